@@ -1,11 +1,10 @@
-use crate::utils::{lines1, whitespace_surrounded, SolverResult};
+use crate::utils::{lines1, whitespace_surrounded, AnswerResult, Params, Part};
 use nom::branch::alt;
 use nom::character::complete::isize;
 use nom::combinator::all_consuming;
 use nom::{bytes::complete::tag, IResult, Parser};
-use std::fs::read_to_string;
 
-type Instruction = isize;
+pub type Instruction = isize;
 
 fn instruction(input: &str) -> IResult<&str, Instruction> {
     let (input, direction) = alt((tag("L"), tag("R"))).parse(input)?;
@@ -20,18 +19,75 @@ fn instruction(input: &str) -> IResult<&str, Instruction> {
     Ok((input, signed_distance))
 }
 
-fn parse_instructions(input: &str) -> IResult<&str, Vec<Instruction>> {
+pub fn parse_instructions(input: &str) -> IResult<&str, Vec<Instruction>> {
     all_consuming(whitespace_surrounded(lines1(instruction))).parse(input)
 }
 
 const INITIAL_POINTER: isize = 50;
 const DIAL_SIZE: isize = 100;
 
-fn part_1(instructions: &Vec<Instruction>) -> usize {
-    let mut pointer: isize = INITIAL_POINTER;
+/// The shape of the safe's dial: how many positions it has, and where the pointer starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dial {
+    pub size: isize,
+    pub initial: isize,
+}
+
+impl Default for Dial {
+    fn default() -> Self {
+        Dial {
+            size: DIAL_SIZE,
+            initial: INITIAL_POINTER,
+        }
+    }
+}
+
+impl Dial {
+    /// The default dial, with its size and starting position overridable
+    /// by the `dial_size` and `initial` parameters.
+    pub fn from_params(params: &Params) -> Result<Self, String> {
+        params.only(&["dial_size", "initial"])?;
+
+        let dial = Dial {
+            size: params.get("dial_size", DIAL_SIZE)?,
+            initial: params.get("initial", INITIAL_POINTER)?,
+        };
+        if dial.size < 1 || !(0..dial.size).contains(&dial.initial) {
+            return Err(format!(
+                "The pointer can't start at {} on a dial of size {}",
+                dial.initial, dial.size
+            ));
+        }
+
+        Ok(dial)
+    }
+
+    fn turn(&self, pointer: isize, instr: Instruction) -> isize {
+        // Widen first, so that a dial near `isize::MAX` in size can't overflow on the way round
+        let size = self.size as i128;
+        ((pointer as i128 + instr.rem_euclid(self.size) as i128) % size) as isize
+    }
+
+    /// How many times the pointer lands on zero while turning from `pointer` by `instr`.
+    fn zero_crossings(&self, pointer: isize, instr: Instruction) -> usize {
+        // Turning left from `pointer` is the same as turning right from its mirror image
+        let start = if instr < 0 {
+            (self.size - pointer) % self.size
+        } else {
+            pointer
+        } as usize;
+        let size = self.size as usize;
+        let distance = instr.unsigned_abs();
+
+        distance / size + (start + distance % size) / size
+    }
+}
+
+pub fn part_1(instructions: &[Instruction], dial: Dial) -> usize {
+    let mut pointer = dial.initial.rem_euclid(dial.size);
     let mut counter = 0;
-    for instr in instructions {
-        pointer = (pointer + instr).rem_euclid(DIAL_SIZE);
+    for &instr in instructions {
+        pointer = dial.turn(pointer, instr);
         if pointer == 0 {
             counter += 1;
         }
@@ -40,36 +96,31 @@ fn part_1(instructions: &Vec<Instruction>) -> usize {
     counter
 }
 
-fn part_2(instructions: &Vec<Instruction>) -> isize {
-    let mut pointer: isize = INITIAL_POINTER;
+pub fn part_2(instructions: &[Instruction], dial: Dial) -> usize {
+    let mut pointer = dial.initial.rem_euclid(dial.size);
     let mut counter = 0;
-    for instr in instructions {
-        // Could be more clever here, but oh well
-        let sgn = instr.signum();
-        for _ in 0..instr.abs() {
-            pointer = (pointer + sgn).rem_euclid(DIAL_SIZE);
-            if pointer == 0 {
-                counter += 1;
-            }
-        }
+    for &instr in instructions {
+        counter += dial.zero_crossings(pointer, instr);
+        pointer = dial.turn(pointer, instr);
     }
 
     counter
 }
 
-pub fn solve() -> SolverResult {
-    let input = read_to_string("inputs/day_01.txt")?;
-    let (_, parsed) = parse_instructions(&input).map_err(|e| e.to_string())?;
-
-    println!("Part 1: {}", part_1(&parsed));
-    println!("Part 2: {}", part_2(&parsed));
+pub fn solve(part: Part, input: &str, params: &Params) -> AnswerResult {
+    let dial = Dial::from_params(params)?;
+    let (_, parsed) = parse_instructions(input).map_err(|e| e.to_string())?;
 
-    Ok(())
+    Ok(match part {
+        Part::One => part_1(&parsed, dial).into(),
+        Part::Two => part_2(&parsed, dial).into(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE: &str = r"
 L68
@@ -87,12 +138,66 @@ L82
     #[test]
     fn test_part_1_example() {
         let (_, instructions) = parse_instructions(EXAMPLE).unwrap();
-        assert_eq!(part_1(&instructions), 3);
+        assert_eq!(part_1(&instructions, Dial::default()), 3);
     }
 
     #[test]
     fn test_part_2_example() {
         let (_, instructions) = parse_instructions(EXAMPLE).unwrap();
-        assert_eq!(part_2(&instructions), 6);
+        assert_eq!(part_2(&instructions, Dial::default()), 6);
+    }
+
+    fn part_2_brute_force(instructions: &[Instruction], dial: Dial) -> usize {
+        let mut pointer = dial.initial.rem_euclid(dial.size);
+        let mut counter = 0;
+        for instr in instructions {
+            let sgn = instr.signum();
+            for _ in 0..instr.abs() {
+                pointer = (pointer + sgn).rem_euclid(dial.size);
+                if pointer == 0 {
+                    counter += 1;
+                }
+            }
+        }
+
+        counter
+    }
+
+    #[test]
+    fn test_part_2_huge_rotations() {
+        assert_eq!(
+            part_2(&[5_000_000_000, -5_000_000_000], Dial::default()),
+            100_000_000
+        );
+        assert_eq!(
+            part_2(&[isize::MAX], Dial::default()),
+            92_233_720_368_547_758
+        );
+    }
+
+    #[test]
+    fn test_huge_dial() {
+        let params = Params::new()
+            .with("dial_size", isize::MAX)
+            .with("initial", isize::MAX - 1);
+
+        assert_eq!(solve(Part::One, "R5", &params).unwrap().to_string(), "0");
+        assert_eq!(solve(Part::One, "R1", &params).unwrap().to_string(), "1");
+        assert_eq!(
+            solve(Part::Two, "R5\nL5", &params).unwrap().to_string(),
+            "2"
+        );
+    }
+
+    proptest! {
+        #[test]
+        fn test_part_2_matches_brute_force(
+            instructions in prop::collection::vec(-1000isize..=1000, 0..50),
+            size in 1isize..150,
+            initial in -200isize..200,
+        ) {
+            let dial = Dial { size, initial };
+            prop_assert_eq!(part_2(&instructions, dial), part_2_brute_force(&instructions, dial));
+        }
     }
 }
@@ -1,77 +1,90 @@
-use crate::utils::{whitespace_surrounded, SolverResult};
+use crate::utils::intervals::range;
+use crate::utils::{whitespace_surrounded, AnswerResult, IntervalSet, Params, Part};
 use nom::bytes::complete::tag;
-use nom::character::complete::usize;
 use nom::multi::separated_list0;
-use nom::sequence::separated_pair;
 use nom::IResult;
 use nom::Parser;
-use std::fs::read_to_string;
 use std::ops::RangeInclusive;
 
-fn range(input: &str) -> IResult<&str, RangeInclusive<usize>> {
-    let (input, (start, end)) = separated_pair(usize, tag("-"), usize).parse(input)?;
+pub fn ranges(input: &str) -> IResult<&str, IntervalSet> {
+    let (input, ranges) = whitespace_surrounded(separated_list0(tag(","), range)).parse(input)?;
 
-    Ok((input, start..=end))
+    Ok((input, ranges.into_iter().collect()))
 }
 
-fn ranges(input: &str) -> IResult<&str, Vec<RangeInclusive<usize>>> {
-    whitespace_surrounded(separated_list0(tag(","), range)).parse(input)
-}
+/// The most digits a `usize` can have.
+const MAX_DIGITS: u32 = usize::MAX.ilog10() + 1;
 
-fn is_invalid_part_1(num: usize) -> bool {
-    let chars = num.to_string().chars().collect::<Vec<char>>();
+/// The sum of the `digits`-digit numbers in `range` that are some `period`-digit pattern repeated.
+///
+/// Every such number is the pattern times the "repunit" `1..01..01`,
+/// so we only need the range of patterns whose multiples land inside `range`.
+fn sum_repeated(range: &RangeInclusive<usize>, digits: u32, period: u32) -> u128 {
+    let repunit = (10u128.pow(digits) - 1) / (10u128.pow(period) - 1);
 
-    let len = chars.len();
-    if len % 2 != 0 {
-        false
-    } else {
-        let midpoint = len / 2;
-        chars[..midpoint] == chars[midpoint..]
+    let lo = (*range.start() as u128).max(10u128.pow(digits - 1));
+    let hi = (*range.end() as u128).min(10u128.pow(digits) - 1);
+    if lo > hi {
+        return 0;
     }
-}
 
-fn all_equal<T: PartialEq, I: IntoIterator<Item = T>>(iter: I) -> bool {
-    let mut iter = iter.into_iter();
-    if let Some(first) = iter.next() {
-        iter.all(|x| x == first)
-    } else {
-        true
+    let pattern_lo = lo.div_ceil(repunit).max(10u128.pow(period - 1));
+    let pattern_hi = (hi / repunit).min(10u128.pow(period) - 1);
+    if pattern_lo > pattern_hi {
+        return 0;
     }
-}
-
-fn is_invalid_part_2(num: usize) -> bool {
-    let chars = num.to_string().chars().collect::<Vec<char>>();
 
-    (1..=chars.len() / 2).any(|chunk_size| all_equal(chars.chunks(chunk_size)))
+    repunit * (pattern_lo + pattern_hi) * (pattern_hi - pattern_lo + 1) / 2
 }
 
-fn part_1(ranges: &[RangeInclusive<usize>]) -> usize {
+pub fn part_1(ranges: &IntervalSet) -> u128 {
     ranges
         .iter()
-        .flat_map(|r| r.clone().filter(|&n| is_invalid_part_1(n)))
+        .flat_map(|r| {
+            (2..=MAX_DIGITS)
+                .step_by(2)
+                .map(move |digits| sum_repeated(r, digits, digits / 2))
+        })
         .sum()
 }
 
-fn part_2(ranges: &[RangeInclusive<usize>]) -> usize {
+/// The sum of the `digits`-digit numbers in `range` made of a pattern repeated at least twice.
+fn sum_repeated_any(range: &RangeInclusive<usize>, digits: u32) -> u128 {
+    // Numbers that repeat with period `p` also repeat with every multiple of `p` that divides `digits`,
+    // so count each number only under its shortest period.
+    let mut shortest = vec![0; digits as usize];
+    for period in (1..digits).filter(|&p| digits.is_multiple_of(p)) {
+        shortest[period as usize] = sum_repeated(range, digits, period)
+            - (1..period)
+                .filter(|&p| period.is_multiple_of(p))
+                .map(|p| shortest[p as usize])
+                .sum::<u128>();
+    }
+
+    shortest.iter().sum()
+}
+
+pub fn part_2(ranges: &IntervalSet) -> u128 {
     ranges
         .iter()
-        .flat_map(|r| r.clone().filter(|&n| is_invalid_part_2(n)))
+        .flat_map(|r| (1..=MAX_DIGITS).map(move |digits| sum_repeated_any(r, digits)))
         .sum()
 }
 
-pub fn solve() -> SolverResult {
-    let input = read_to_string("inputs/day_02.txt")?;
-    let (_, ranges) = ranges(&input).map_err(|e| e.to_string())?;
+pub fn solve(part: Part, input: &str, params: &Params) -> AnswerResult {
+    params.only(&[])?;
+    let (_, ranges) = ranges(input).map_err(|e| e.to_string())?;
 
-    println!("Part 1: {}", part_1(&ranges));
-    println!("Part 2: {}", part_2(&ranges));
-
-    Ok(())
+    Ok(match part {
+        Part::One => part_1(&ranges).into(),
+        Part::Two => part_2(&ranges).into(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE: &str = r"11-22,95-115,998-1012,1188511880-1188511890,222220-222224,1698522-1698528,446443-446449,38593856-38593862,565653-565659,824824821-824824827,2121212118-2121212124";
 
@@ -86,4 +99,70 @@ mod tests {
         let (_, ranges) = ranges(EXAMPLE).unwrap();
         assert_eq!(part_2(&ranges), 4174379265);
     }
+
+    fn is_invalid_part_1(num: usize) -> bool {
+        let chars = num.to_string().chars().collect::<Vec<char>>();
+
+        let len = chars.len();
+        if len % 2 != 0 {
+            false
+        } else {
+            let midpoint = len / 2;
+            chars[..midpoint] == chars[midpoint..]
+        }
+    }
+
+    fn all_equal<T: PartialEq, I: IntoIterator<Item = T>>(iter: I) -> bool {
+        let mut iter = iter.into_iter();
+        if let Some(first) = iter.next() {
+            iter.all(|x| x == first)
+        } else {
+            true
+        }
+    }
+
+    fn is_invalid_part_2(num: usize) -> bool {
+        let chars = num.to_string().chars().collect::<Vec<char>>();
+
+        (1..=chars.len() / 2).any(|chunk_size| all_equal(chars.chunks(chunk_size)))
+    }
+
+    #[test]
+    fn test_two_digit_numbers() {
+        let ranges = IntervalSet::from_iter([0..=99]);
+
+        assert_eq!(part_1(&ranges), 495);
+        assert_eq!(part_2(&ranges), 495);
+    }
+
+    #[test]
+    fn test_shortest_period_is_counted_once() {
+        let ranges = IntervalSet::from_iter([111111..=111111, 121212..=121212]);
+
+        assert_eq!(part_1(&ranges), 111111);
+        assert_eq!(part_2(&ranges), 111111 + 121212);
+    }
+
+    #[test]
+    fn test_everything() {
+        let ranges = IntervalSet::from_iter([0..=usize::MAX]);
+
+        assert!(part_2(&ranges) > part_1(&ranges));
+    }
+
+    proptest! {
+        #[test]
+        fn test_parts_match_brute_force(start in 0usize..100_000_000, width in 0usize..2000) {
+            let ranges = IntervalSet::from_iter([start..=start + width]);
+
+            prop_assert_eq!(
+                part_1(&ranges),
+                (start..=start + width).filter(|&n| is_invalid_part_1(n)).sum::<usize>() as u128
+            );
+            prop_assert_eq!(
+                part_2(&ranges),
+                (start..=start + width).filter(|&n| is_invalid_part_2(n)).sum::<usize>() as u128
+            );
+        }
+    }
 }
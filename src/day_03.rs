@@ -1,48 +1,108 @@
-use crate::utils::SolverResult;
-use itertools::Itertools;
-use std::fs::read_to_string;
-
-fn largest_joltage(bank: &str, count: usize) -> usize {
-    let batteries = bank.chars().collect_vec();
-
-    let mut turned_on: Vec<char> = vec![];
-    let mut start = 0;
-
-    for n in (0..count).rev() {
-        let (max_battery_idx, max_battery) = batteries[start..batteries.len() - n]
-            .iter()
-            .enumerate()
-            .rev() // Iterate from the end to find the *first* maximum value instead of the last
-            .max_by_key(|(_, battery)| *battery)
-            .unwrap();
-
-        turned_on.push(*max_battery);
-        start += max_battery_idx + 1; // += because the enumerated indices are relative to the sliced array starting at `start`
+use crate::utils::{AnswerResult, Params, Part};
+use num::BigUint;
+
+pub type Bank = Vec<u8>;
+
+pub fn parse_bank(line: &str) -> Result<Bank, String> {
+    line.chars()
+        .enumerate()
+        .map(|(idx, ch)| {
+            ch.to_digit(10).map(|d| d as u8).ok_or_else(|| {
+                format!(
+                    "Invalid battery {:?} at position {} in bank {:?}",
+                    ch,
+                    idx + 1,
+                    line
+                )
+            })
+        })
+        .collect()
+}
+
+pub fn parse_input(input: &str) -> Result<Vec<Bank>, String> {
+    input.lines().map(parse_bank).collect()
+}
+
+/// The batteries turned on in a bank, in order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Selection {
+    pub digits: Vec<u8>,
+    pub indices: Vec<usize>,
+}
+
+impl Selection {
+    /// The joltage of the selected batteries, read as a decimal number.
+    pub fn value(&self) -> Result<BigUint, String> {
+        BigUint::from_radix_be(&self.digits, 10)
+            .ok_or_else(|| format!("Batteries {:?} aren't all decimal digits", self.digits))
     }
+}
 
-    turned_on.into_iter().join("").parse().unwrap()
+pub fn largest_joltage(bank: &[u8], count: usize) -> Result<Selection, String> {
+    if count > bank.len() {
+        return Err(format!(
+            "Can't turn on {} batteries in a bank of {}",
+            count,
+            bank.len()
+        ));
+    }
+
+    // Keep a stack of the chosen batteries, kicking out smaller ones whenever a bigger one comes along,
+    // as long as there are enough batteries left to still fill the stack
+    let mut chosen: Vec<usize> = Vec::with_capacity(count);
+    for (idx, &battery) in bank.iter().enumerate() {
+        while let Some(&last) = chosen.last() {
+            if bank[last] < battery && chosen.len() - 1 + bank.len() - idx >= count {
+                chosen.pop();
+            } else {
+                break;
+            }
+        }
+        if chosen.len() < count {
+            chosen.push(idx);
+        }
+    }
+
+    Ok(Selection {
+        digits: chosen.iter().map(|&idx| bank[idx]).collect(),
+        indices: chosen,
+    })
 }
 
-fn part_1(banks: &[&str]) -> usize {
-    banks.iter().map(|bank| largest_joltage(bank, 2)).sum()
+fn total_joltage(banks: &[Bank], count: usize) -> Result<BigUint, String> {
+    banks
+        .iter()
+        .map(|bank| largest_joltage(bank, count)?.value())
+        .sum()
 }
 
-fn part_2(banks: &[&str]) -> usize {
-    banks.iter().map(|bank| largest_joltage(bank, 12)).sum()
+const PART_1_BATTERIES: usize = 2;
+const PART_2_BATTERIES: usize = 12;
+
+pub fn part_1(banks: &[Bank]) -> Result<BigUint, String> {
+    total_joltage(banks, PART_1_BATTERIES)
+}
+
+pub fn part_2(banks: &[Bank]) -> Result<BigUint, String> {
+    total_joltage(banks, PART_2_BATTERIES)
 }
 
-pub fn solve() -> SolverResult {
-    let input = read_to_string("inputs/day_03.txt")?;
-    let banks = input.lines().collect_vec();
+pub fn solve(part: Part, input: &str, params: &Params) -> AnswerResult {
+    params.only(&["part_1_batteries", "part_2_batteries"])?;
+    let banks = parse_input(input)?;
 
-    println!("Part 1: {}", part_1(&banks));
-    println!("Part 2: {}", part_2(&banks));
+    let total = match part {
+        Part::One => total_joltage(&banks, params.get("part_1_batteries", PART_1_BATTERIES)?)?,
+        Part::Two => total_joltage(&banks, params.get("part_2_batteries", PART_2_BATTERIES)?)?,
+    };
 
-    Ok(())
+    Ok(total.into())
 }
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+    use proptest::prelude::*;
     use rstest::rstest;
 
     use super::*;
@@ -53,8 +113,12 @@ mod tests {
     #[case("234234234234278", 78)]
     #[case("818181911112111", 92)]
     #[case("919000000000000", 99)]
-    fn test_largest_joltage_part_1(#[case] batteries: &str, #[case] expected: usize) {
-        assert_eq!(largest_joltage(batteries, 2), expected);
+    fn test_largest_joltage_part_1(#[case] batteries: &str, #[case] expected: u64) {
+        let bank = parse_bank(batteries).unwrap();
+        assert_eq!(
+            largest_joltage(&bank, 2).unwrap().value().unwrap(),
+            BigUint::from(expected)
+        );
     }
 
     #[rstest]
@@ -62,7 +126,79 @@ mod tests {
     #[case("811111111111119", 811111111119)]
     #[case("234234234234278", 434234234278)]
     #[case("818181911112111", 888911112111)]
-    fn test_largest_joltage_part_2(#[case] batteries: &str, #[case] expected: usize) {
-        assert_eq!(largest_joltage(batteries, 12), expected);
+    fn test_largest_joltage_part_2(#[case] batteries: &str, #[case] expected: u64) {
+        let bank = parse_bank(batteries).unwrap();
+        assert_eq!(
+            largest_joltage(&bank, 12).unwrap().value().unwrap(),
+            BigUint::from(expected)
+        );
+    }
+
+    #[test]
+    fn test_largest_joltage_indices() {
+        let bank = parse_bank("818181911112111").unwrap();
+        let selection = largest_joltage(&bank, 4).unwrap();
+
+        assert_eq!(selection.digits, vec![9, 2, 1, 1]);
+        assert_eq!(selection.indices, vec![6, 11, 12, 13]);
+    }
+
+    #[test]
+    fn test_largest_joltage_more_digits_than_fit_in_u128() {
+        let bank = parse_bank(&"91".repeat(30)).unwrap();
+        let selection = largest_joltage(&bank, 45).unwrap();
+
+        assert_eq!(
+            selection.value().unwrap().to_string(),
+            format!("{}{}", "9".repeat(15), "91".repeat(15))
+        );
+    }
+
+    #[test]
+    fn test_selection_with_bad_digit() {
+        let selection = Selection {
+            digits: vec![9, 10],
+            indices: vec![0, 1],
+        };
+
+        assert_eq!(
+            selection.value(),
+            Err("Batteries [9, 10] aren't all decimal digits".to_string())
+        );
+    }
+
+    #[test]
+    fn test_too_many_batteries() {
+        let bank = parse_bank("123").unwrap();
+        assert!(largest_joltage(&bank, 4).is_err());
+    }
+
+    #[test]
+    fn test_malformed_bank() {
+        assert_eq!(
+            parse_bank("12x4"),
+            Err("Invalid battery 'x' at position 3 in bank \"12x4\"".to_string())
+        );
+    }
+
+    fn largest_joltage_brute_force(bank: &str, count: usize) -> usize {
+        bank.chars()
+            .combinations(count)
+            .map(|batteries| batteries.into_iter().join("").parse().unwrap())
+            .max()
+            .unwrap()
+    }
+
+    proptest! {
+        #[test]
+        fn test_largest_joltage_matches_brute_force(
+            (bank, count) in "[1-9]{1,12}".prop_flat_map(|bank| {
+                let len = bank.len();
+                (Just(bank), 1..=len)
+            })
+        ) {
+            let selection = largest_joltage(&parse_bank(&bank).unwrap(), count).unwrap();
+            prop_assert_eq!(selection.value().unwrap(), BigUint::from(largest_joltage_brute_force(&bank, count)));
+        }
     }
 }
@@ -1,80 +1,256 @@
-use crate::utils::SolverResult;
-use std::collections::HashSet;
-use std::fs::read_to_string;
-use std::ops::Sub;
+use crate::utils::{AnswerResult, Grid, Params, Part, Position};
+use crate::visualization::{Frame, VisualizerResult, DIM, RED, WHITE};
+use itertools::Itertools;
 
-type PaperGrid = HashSet<(isize, isize)>;
+pub type PaperGrid = Grid<bool>;
 
-fn parse_input(input: &str) -> PaperGrid {
-    let mut grid = PaperGrid::new();
+pub fn parse_input(input: &str) -> Result<PaperGrid, String> {
+    Grid::parse(input, |ch| ch == '@')
+}
+
+/// Which cells count as a cell's neighbours.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Neighbourhood {
+    /// Every cell within `radius` steps horizontally and vertically, including diagonals.
+    Moore { radius: usize },
+    /// Every cell within a Manhattan distance of `radius`.
+    VonNeumann { radius: usize },
+    /// The six neighbours of a hexagonal grid stored in axial coordinates.
+    Hexagonal,
+    /// Arbitrary `(dx, dy)` offsets, which don't have to be symmetric.
+    Custom(Vec<(isize, isize)>),
+}
 
-    for (y, line) in input.lines().enumerate() {
-        for (x, ch) in line.chars().enumerate() {
-            if ch == '@' {
-                grid.insert((x as isize, y as isize));
+impl Neighbourhood {
+    pub fn offsets(&self) -> Vec<(isize, isize)> {
+        match self {
+            Neighbourhood::Moore { radius } => {
+                let r = *radius as isize;
+                (-r..=r)
+                    .cartesian_product(-r..=r)
+                    .filter(|&(dy, dx)| (dx, dy) != (0, 0))
+                    .map(|(dy, dx)| (dx, dy))
+                    .collect()
             }
+            Neighbourhood::VonNeumann { radius } => {
+                let r = *radius as isize;
+                (-r..=r)
+                    .cartesian_product(-r..=r)
+                    .filter(|&(dy, dx)| (dx, dy) != (0, 0) && dx.abs() + dy.abs() <= r)
+                    .map(|(dy, dx)| (dx, dy))
+                    .collect()
+            }
+            Neighbourhood::Hexagonal => vec![(0, -1), (1, -1), (-1, 0), (1, 0), (-1, 1), (0, 1)],
+            Neighbourhood::Custom(offsets) => offsets.clone(),
         }
     }
+}
 
-    grid
+/// What happens to neighbours that fall off the edge of the grid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Boundary {
+    /// Off the edge is empty.
+    Bounded,
+    /// The grid wraps around like a torus.
+    Toroidal,
 }
 
-const DX_DY: [(isize, isize); 8] = [
-    (-1, -1),
-    (0, -1),
-    (1, -1),
-    (-1, 0),
-    (1, 0),
-    (-1, 1),
-    (0, 1),
-    (1, 1),
-];
+/// The rules for when a roll can be removed: it must have fewer than `threshold` neighbouring rolls.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rules {
+    pub neighbourhood: Neighbourhood,
+    pub threshold: usize,
+    pub boundary: Boundary,
+}
 
-fn count_occupied_neighbours(grid: &PaperGrid, (x, y): &(isize, isize)) -> usize {
-    DX_DY
-        .iter()
-        .filter(|(dx, dy)| grid.contains(&(x + dx, y + dy)))
-        .count()
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            neighbourhood: Neighbourhood::Moore { radius: 1 },
+            threshold: 4,
+            boundary: Boundary::Bounded,
+        }
+    }
 }
 
-fn part_1(grid: &PaperGrid) -> usize {
-    grid.iter()
-        .filter(|&xy| count_occupied_neighbours(grid, xy) < 4)
-        .count()
+/// The rules, with the neighbourhood's offsets worked out once up front.
+struct Stencil {
+    offsets: Vec<(isize, isize)>,
+    threshold: usize,
+    boundary: Boundary,
 }
 
-fn part_2(grid: &PaperGrid) -> usize {
-    let initial_occupied = grid.len();
-    let mut grid = grid.clone();
-    loop {
-        let removed_rolls: PaperGrid = grid
+impl Stencil {
+    fn new(rules: &Rules) -> Self {
+        Stencil {
+            offsets: rules.neighbourhood.offsets(),
+            threshold: rules.threshold,
+            boundary: rules.boundary,
+        }
+    }
+
+    fn shift(&self, grid: &PaperGrid, xy: Position, offset: (isize, isize)) -> Option<Position> {
+        match self.boundary {
+            Boundary::Bounded => grid.offset(xy, offset),
+            Boundary::Toroidal => Some(grid.wrapping_offset(xy, offset)),
+        }
+    }
+
+    /// The cells that `xy` counts as neighbours.
+    fn neighbours<'a>(
+        &'a self,
+        grid: &'a PaperGrid,
+        xy: Position,
+    ) -> impl Iterator<Item = Position> + 'a {
+        self.offsets
+            .iter()
+            .filter_map(move |&offset| self.shift(grid, xy, offset))
+    }
+
+    /// The cells that count `xy` as one of their neighbours.
+    /// These differ from `neighbours` when the offsets aren't symmetric.
+    fn dependents<'a>(
+        &'a self,
+        grid: &'a PaperGrid,
+        xy: Position,
+    ) -> impl Iterator<Item = Position> + 'a {
+        self.offsets
             .iter()
-            .filter_map(|xy| (count_occupied_neighbours(&grid, xy) < 4).then_some(*xy))
-            .collect();
+            .filter_map(move |&(dx, dy)| self.shift(grid, xy, (-dx, -dy)))
+    }
+
+    fn count_occupied_neighbours(&self, grid: &PaperGrid, xy: Position) -> usize {
+        self.neighbours(grid, xy).filter(|&n| grid[n]).count()
+    }
+
+    fn accessible_rolls(&self, grid: &PaperGrid) -> Vec<Position> {
+        grid.iter()
+            .filter(|&(xy, &occupied)| {
+                occupied && self.count_occupied_neighbours(grid, xy) < self.threshold
+            })
+            .map(|(xy, _)| xy)
+            .collect()
+    }
+}
+
+pub fn part_1(grid: &PaperGrid, rules: &Rules) -> usize {
+    Stencil::new(rules).accessible_rolls(grid).len()
+}
+
+/// Which rolls get removed in which wave, as rolls become accessible once their neighbours are gone.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovalSchedule {
+    /// The rolls removed in each wave, in reading order.
+    pub waves: Vec<Vec<Position>>,
+    removed_in: Grid<Option<usize>>,
+}
+
+impl RemovalSchedule {
+    pub fn total(&self) -> usize {
+        self.waves.iter().map(|wave| wave.len()).sum()
+    }
+
+    pub fn wave_count(&self) -> usize {
+        self.waves.len()
+    }
+
+    /// The wave that removed the roll at `xy`, if it was ever removed.
+    pub fn removed_in(&self, xy: Position) -> Option<usize> {
+        self.removed_in.get(xy).copied().flatten()
+    }
+}
+
+pub fn removal_schedule(grid: &PaperGrid, rules: &Rules) -> RemovalSchedule {
+    let stencil = Stencil::new(rules);
 
-        if removed_rolls.is_empty() {
-            break;
-        } else {
-            grid = grid.sub(&removed_rolls);
+    let mut neighbours = grid.map(|_| 0);
+    for (xy, &occupied) in grid.iter() {
+        if occupied {
+            neighbours[xy] = stencil.count_occupied_neighbours(grid, xy);
         }
     }
 
-    initial_occupied - grid.len()
+    let mut removed_in = grid.map(|_| None);
+    let mut queued = grid.map(|_| false);
+    let mut waves = vec![];
+
+    let mut wave = stencil.accessible_rolls(grid);
+    while !wave.is_empty() {
+        for &xy in &wave {
+            removed_in[xy] = Some(waves.len());
+        }
+
+        // Only the rolls next to the ones that just went can have become accessible
+        let mut next = vec![];
+        for &xy in &wave {
+            for n in stencil.dependents(grid, xy) {
+                if grid[n] && removed_in[n].is_none() {
+                    neighbours[n] -= 1;
+                    if neighbours[n] < stencil.threshold && !queued[n] {
+                        queued[n] = true;
+                        next.push(n);
+                    }
+                }
+            }
+        }
+        next.sort_by_key(|&(x, y)| (y, x));
+
+        waves.push(wave);
+        wave = next;
+    }
+
+    RemovalSchedule { waves, removed_in }
 }
 
-pub fn solve() -> SolverResult {
-    let input = read_to_string("inputs/day_04.txt").unwrap();
-    let grid = parse_input(&input);
+pub fn part_2(grid: &PaperGrid, rules: &Rules) -> usize {
+    removal_schedule(grid, rules).total()
+}
 
-    println!("Part 1: {}", part_1(&grid));
-    println!("Part 2: {}", part_2(&grid));
+fn removal_frames(grid: &PaperGrid, rules: &Rules) -> Vec<Frame> {
+    let mut frame = Frame::new(grid.width(), grid.height());
+    for (xy, &occupied) in grid.iter() {
+        if occupied {
+            frame.set(xy.0, xy.1, WHITE);
+        }
+    }
+
+    let mut frames = vec![frame.clone()];
+    for wave in removal_schedule(grid, rules).waves {
+        for &(x, y) in &wave {
+            frame.set(x, y, RED);
+        }
+        frames.push(frame.clone());
+        for &(x, y) in &wave {
+            frame.set(x, y, DIM);
+        }
+    }
+    frames.push(frame);
+
+    frames
+}
+
+pub fn solve(part: Part, input: &str, params: &Params) -> AnswerResult {
+    params.only(&["threshold"])?;
+    let rules = Rules {
+        threshold: params.get("threshold", Rules::default().threshold)?,
+        ..Rules::default()
+    };
+    let grid = parse_input(input)?;
+
+    Ok(match part {
+        Part::One => part_1(&grid, &rules).into(),
+        Part::Two => part_2(&grid, &rules).into(),
+    })
+}
 
-    Ok(())
+pub fn visualize(input: &str) -> VisualizerResult {
+    Ok(removal_frames(&parse_input(input)?, &Rules::default()))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
 
     const EXAMPLE: &str = "
 ..@@.@@@@.
@@ -90,13 +266,133 @@ mod tests {
 
     #[test]
     fn test_part_1_example() {
-        let grid = parse_input(EXAMPLE.trim());
-        assert_eq!(part_1(&grid), 13);
+        let grid = parse_input(EXAMPLE).unwrap();
+        assert_eq!(part_1(&grid, &Rules::default()), 13);
     }
 
     #[test]
     fn test_part_2_example() {
-        let grid = parse_input(EXAMPLE.trim());
-        assert_eq!(part_2(&grid), 43);
+        let grid = parse_input(EXAMPLE).unwrap();
+        assert_eq!(part_2(&grid, &Rules::default()), 43);
+    }
+
+    #[test]
+    fn test_removal_frames_example() {
+        let grid = parse_input(EXAMPLE).unwrap();
+        let frames = removal_frames(&grid, &Rules::default());
+
+        assert_eq!(
+            frames.len(),
+            removal_schedule(&grid, &Rules::default()).wave_count() + 2
+        );
+        assert_eq!(frames[0].get(2, 0), Some(WHITE));
+        assert_eq!(frames[1].get(2, 0), Some(RED));
+        assert_eq!(frames[2].get(2, 0), Some(DIM));
+    }
+
+    #[test]
+    fn test_removal_schedule_example() {
+        let grid = parse_input(EXAMPLE).unwrap();
+        let schedule = removal_schedule(&grid, &Rules::default());
+
+        assert_eq!(schedule.total(), 43);
+        assert_eq!(schedule.waves[0].len(), 13);
+        assert_eq!(schedule.removed_in((2, 0)), Some(0));
+        assert_eq!(schedule.removed_in((0, 0)), None);
+        assert_eq!(schedule.removed_in((4, 4)), None);
+    }
+
+    fn removal_waves_brute_force(grid: &PaperGrid, rules: &Rules) -> Vec<Vec<Position>> {
+        let stencil = Stencil::new(rules);
+        let mut grid = grid.clone();
+        let mut waves = vec![];
+        loop {
+            let removed_rolls = stencil.accessible_rolls(&grid);
+
+            if removed_rolls.is_empty() {
+                break;
+            } else {
+                for &xy in &removed_rolls {
+                    grid[xy] = false;
+                }
+                waves.push(removed_rolls);
+            }
+        }
+
+        waves
+    }
+
+    fn grid_strategy() -> impl Strategy<Value = PaperGrid> {
+        (1usize..15, 1usize..15).prop_flat_map(|(width, height)| {
+            prop::collection::vec(prop::bool::weighted(0.7), width * height).prop_map(
+                move |cells| {
+                    let art = cells
+                        .chunks(width)
+                        .map(|row| {
+                            row.iter()
+                                .map(|&c| if c { '@' } else { '.' })
+                                .collect::<String>()
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n");
+                    parse_input(&art).unwrap()
+                },
+            )
+        })
+    }
+
+    fn rules_strategy() -> impl Strategy<Value = Rules> {
+        let neighbourhood = prop_oneof![
+            (1usize..3).prop_map(|radius| Neighbourhood::Moore { radius }),
+            (1usize..3).prop_map(|radius| Neighbourhood::VonNeumann { radius }),
+            Just(Neighbourhood::Hexagonal),
+            prop::collection::vec((-2isize..=2, -2isize..=2), 0..6).prop_map(Neighbourhood::Custom),
+        ];
+        let boundary = prop_oneof![Just(Boundary::Bounded), Just(Boundary::Toroidal)];
+
+        (neighbourhood, 0usize..10, boundary).prop_map(|(neighbourhood, threshold, boundary)| {
+            Rules {
+                neighbourhood,
+                threshold,
+                boundary,
+            }
+        })
+    }
+
+    #[test]
+    fn test_neighbourhood_offsets() {
+        assert_eq!(Neighbourhood::Moore { radius: 1 }.offsets().len(), 8);
+        assert_eq!(Neighbourhood::Moore { radius: 2 }.offsets().len(), 24);
+        assert_eq!(
+            Neighbourhood::VonNeumann { radius: 1 }.offsets(),
+            vec![(0, -1), (-1, 0), (1, 0), (0, 1)]
+        );
+        assert_eq!(Neighbourhood::VonNeumann { radius: 2 }.offsets().len(), 12);
+    }
+
+    #[test]
+    fn test_toroidal_example() {
+        let grid = parse_input(EXAMPLE).unwrap();
+        let rules = Rules {
+            boundary: Boundary::Toroidal,
+            ..Rules::default()
+        };
+
+        // Wrapping around gives the edges more neighbours, so fewer rolls are accessible
+        assert!(part_1(&grid, &rules) < part_1(&grid, &Rules::default()));
+    }
+
+    proptest! {
+        #[test]
+        fn test_removal_schedule_matches_brute_force(grid in grid_strategy(), rules in rules_strategy()) {
+            let schedule = removal_schedule(&grid, &rules);
+
+            prop_assert_eq!(&schedule.waves, &removal_waves_brute_force(&grid, &rules));
+            for (wave_idx, wave) in schedule.waves.iter().enumerate() {
+                for &xy in wave {
+                    prop_assert_eq!(schedule.removed_in(xy), Some(wave_idx));
+                }
+            }
+        }
     }
 }
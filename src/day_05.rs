@@ -1,23 +1,16 @@
-use crate::utils::{lines1, whitespace_surrounded, SolverResult};
+use crate::utils::intervals::range;
+use crate::utils::{lines1, whitespace_surrounded, AnswerResult, IntervalSet, Params, Part};
 use itertools::Itertools;
-use nom::bytes::complete::tag;
 use nom::character::complete::multispace1;
 use nom::character::complete::usize;
 use nom::sequence::separated_pair;
 use nom::IResult;
 use nom::Parser;
-use std::fs::read_to_string;
 use std::ops::RangeInclusive;
 
-struct Database {
-    ranges: Vec<RangeInclusive<usize>>,
-    ingredients: Vec<usize>,
-}
-
-fn range(input: &str) -> IResult<&str, RangeInclusive<usize>> {
-    let (input, (start, end)) = separated_pair(usize, tag("-"), usize).parse(input)?;
-
-    Ok((input, start..=end))
+pub struct Database {
+    pub ranges: Vec<RangeInclusive<usize>>,
+    pub ingredients: Vec<usize>,
 }
 
 fn ranges(input: &str) -> IResult<&str, Vec<RangeInclusive<usize>>> {
@@ -28,7 +21,7 @@ fn ingredients(input: &str) -> IResult<&str, Vec<usize>> {
     lines1(usize).parse(input)
 }
 
-fn parse_input(input: &str) -> IResult<&str, Database> {
+pub fn parse_input(input: &str) -> IResult<&str, Database> {
     let (input, (ranges, ingredients)) =
         whitespace_surrounded(separated_pair(ranges, multispace1, ingredients)).parse(input)?;
 
@@ -41,51 +34,138 @@ fn parse_input(input: &str) -> IResult<&str, Database> {
     ))
 }
 
-fn part_1(db: &Database) -> usize {
-    db.ingredients
-        .iter()
-        .filter(|ingredient| db.ranges.iter().any(|range| range.contains(ingredient)))
-        .count()
+impl Database {
+    /// The set of all fresh ingredient IDs.
+    pub fn fresh(&self) -> IntervalSet {
+        self.ranges.iter().cloned().collect()
+    }
 }
 
-fn collapse_ranges(ranges: Vec<RangeInclusive<usize>>) -> Vec<RangeInclusive<usize>> {
-    ranges
-        .iter()
-        .sorted_by_key(|range| range.start())
-        .fold(vec![], |mut acc, range| {
-            if let Some(last) = acc.last_mut() {
-                if range.start() <= last.end() {
-                    *last = *last.start()..=*range.end().max(last.end());
-                } else {
-                    acc.push(range.clone());
-                }
-            } else {
-                acc.push(range.clone());
-            }
-            acc
-        })
+/// Answers questions about ingredient IDs in logarithmic time.
+pub struct FreshIndex {
+    fresh: IntervalSet,
+    /// The original ranges as `(start, end, index in the database)`, sorted by start.
+    by_start: Vec<(usize, usize, usize)>,
+    /// A segment tree over `by_start` holding the largest end in each node's span.
+    max_end: Vec<usize>,
+}
+
+impl FreshIndex {
+    pub fn new(ranges: &[RangeInclusive<usize>]) -> Self {
+        let by_start = ranges
+            .iter()
+            .enumerate()
+            .filter(|(_, r)| !r.is_empty())
+            .map(|(idx, r)| (*r.start(), *r.end(), idx))
+            .sorted()
+            .collect_vec();
+
+        let mut index = FreshIndex {
+            fresh: ranges.iter().cloned().collect(),
+            max_end: vec![0; 4 * by_start.len().max(1)],
+            by_start,
+        };
+        if !index.by_start.is_empty() {
+            index.build(1, 0, index.by_start.len() - 1);
+        }
+
+        index
+    }
+
+    fn build(&mut self, node: usize, lo: usize, hi: usize) -> usize {
+        self.max_end[node] = if lo == hi {
+            self.by_start[lo].1
+        } else {
+            let mid = (lo + hi) / 2;
+            self.build(2 * node, lo, mid)
+                .max(self.build(2 * node + 1, mid + 1, hi))
+        };
+
+        self.max_end[node]
+    }
+
+    pub fn is_fresh(&self, id: usize) -> bool {
+        self.fresh.contains(id)
+    }
+
+    /// The indices of the original ranges that contain `id`, in increasing order.
+    pub fn covering(&self, id: usize) -> Vec<usize> {
+        // Only ranges that start at or before `id` can contain it,
+        // and of those, only the ones that end at or after it
+        let candidates = self.by_start.partition_point(|&(start, _, _)| start <= id);
+
+        let mut covering = vec![];
+        if candidates > 0 {
+            self.collect_covering(
+                1,
+                0,
+                self.by_start.len() - 1,
+                candidates - 1,
+                id,
+                &mut covering,
+            );
+        }
+        covering.sort();
+
+        covering
+    }
+
+    fn collect_covering(
+        &self,
+        node: usize,
+        lo: usize,
+        hi: usize,
+        last: usize,
+        id: usize,
+        covering: &mut Vec<usize>,
+    ) {
+        if lo > last || self.max_end[node] < id {
+            return;
+        }
+
+        if lo == hi {
+            covering.push(self.by_start[lo].2);
+        } else {
+            let mid = (lo + hi) / 2;
+            self.collect_covering(2 * node, lo, mid, last, id, covering);
+            self.collect_covering(2 * node + 1, mid + 1, hi, last, id, covering);
+        }
+    }
+
+    /// How far `id` is from the nearest fresh ID, or `None` if nothing is fresh.
+    pub fn distance_to_fresh(&self, id: usize) -> Option<usize> {
+        self.fresh.distance_to(id)
+    }
 }
 
-fn part_2(db: &Database) -> usize {
-    collapse_ranges(db.ranges.clone())
+pub fn part_1(db: &Database) -> usize {
+    let index = FreshIndex::new(&db.ranges);
+
+    db.ingredients
         .iter()
-        .map(|r| r.end() - r.start() + 1)
-        .sum()
+        .filter(|&&ingredient| index.is_fresh(ingredient))
+        .count()
 }
 
-pub fn solve() -> SolverResult {
-    let input = read_to_string("inputs/day_05.txt")?;
-    let (_, db) = parse_input(&input).map_err(|e| e.to_string())?;
+pub fn part_2(db: &Database) -> u128 {
+    db.fresh().total_length()
+}
 
-    println!("Part 1: {}", part_1(&db));
-    println!("Part 2: {}", part_2(&db));
+pub fn solve(part: Part, input: &str, params: &Params) -> AnswerResult {
+    params.only(&[])?;
+    let (_, db) = parse_input(input).map_err(|e| e.to_string())?;
 
-    Ok(())
+    Ok(match part {
+        Part::One => part_1(&db).into(),
+        Part::Two => part_2(&db).into(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
 
     const EXAMPLE: &str = "\
 3-5
@@ -121,4 +201,58 @@ mod tests {
 
         assert_eq!(part_2(&db), 14)
     }
+
+    fn ranges_strategy() -> impl Strategy<Value = Vec<RangeInclusive<usize>>> {
+        prop::collection::vec(
+            (0usize..200, 0usize..20).prop_map(|(start, len)| start..=start + len),
+            0..30,
+        )
+    }
+
+    #[test]
+    fn test_fresh_index_example() {
+        let (_, db) = parse_input(EXAMPLE).unwrap();
+        let index = FreshIndex::new(&db.ranges);
+
+        assert!(index.is_fresh(17));
+        assert!(!index.is_fresh(8));
+        assert_eq!(index.covering(17), vec![2, 3]);
+        assert_eq!(index.covering(5), vec![0]);
+        assert!(index.covering(32).is_empty());
+        assert_eq!(index.distance_to_fresh(32), Some(12));
+        assert_eq!(index.distance_to_fresh(8), Some(2));
+    }
+
+    proptest! {
+        #[test]
+        fn test_fresh_index_matches_brute_force(ranges in ranges_strategy(), ids in prop::collection::vec(0usize..250, 0..20)) {
+            let index = FreshIndex::new(&ranges);
+
+            for id in ids {
+                let covering = ranges
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(idx, r)| r.contains(&id).then_some(idx))
+                    .collect::<Vec<_>>();
+
+                prop_assert_eq!(index.is_fresh(id), !covering.is_empty());
+                prop_assert_eq!(index.covering(id), covering);
+                prop_assert_eq!(
+                    index.distance_to_fresh(id),
+                    ranges
+                        .iter()
+                        .map(|r| if r.contains(&id) { 0 } else { r.start().abs_diff(id).min(r.end().abs_diff(id)) })
+                        .min()
+                );
+            }
+        }
+
+        #[test]
+        fn test_part_2_matches_set_membership(ranges in ranges_strategy()) {
+            let fresh: HashSet<usize> = ranges.iter().flat_map(|r| r.clone()).collect();
+            let db = Database { ranges, ingredients: vec![] };
+
+            prop_assert_eq!(part_2(&db), fresh.len() as u128);
+        }
+    }
 }
@@ -1,131 +1,230 @@
-use crate::utils::SolverResult;
+use crate::utils::{AnswerResult, Grid, Params, Part};
 use itertools::Itertools;
-use nom::branch::alt;
-use nom::bytes::complete::tag;
-use nom::character::complete::space1;
-use nom::character::complete::{multispace1, usize};
-use nom::multi::separated_list1;
-use nom::{IResult, Parser};
-
-#[derive(Debug, PartialEq, Eq)]
-enum Op {
+use std::ops::Range;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Op {
     Add,
+    Sub,
     Mul,
+    Div,
+    Min,
+    Max,
+    Pow,
 }
 
-#[derive(Debug, PartialEq)]
-struct Problems {
-    numbers: Vec<Vec<usize>>,
-    operations: Vec<Op>,
+impl TryFrom<char> for Op {
+    type Error = String;
+
+    fn try_from(symbol: char) -> Result<Self, Self::Error> {
+        match symbol {
+            '+' => Ok(Op::Add),
+            '-' => Ok(Op::Sub),
+            '*' => Ok(Op::Mul),
+            '/' => Ok(Op::Div),
+            '<' => Ok(Op::Min),
+            '>' => Ok(Op::Max),
+            '^' => Ok(Op::Pow),
+            _ => Err(format!("Unknown operation: {:?}", symbol)),
+        }
+    }
 }
 
-fn numbers(input: &str) -> IResult<&str, Vec<usize>> {
-    separated_list1(space1, usize).parse(input)
+impl Op {
+    fn apply(&self, a: i128, b: i128) -> Result<i128, String> {
+        let result = match self {
+            Op::Add => a.checked_add(b),
+            Op::Sub => a.checked_sub(b),
+            Op::Mul => a.checked_mul(b),
+            Op::Div if b == 0 => return Err(format!("Division by zero: {} / {}", a, b)),
+            Op::Div => a.checked_div(b),
+            Op::Min => Some(a.min(b)),
+            Op::Max => Some(a.max(b)),
+            Op::Pow if b < 0 => return Err(format!("Negative exponent: {} ^ {}", a, b)),
+            Op::Pow => u32::try_from(b).ok().and_then(|b| a.checked_pow(b)),
+        };
+
+        result.ok_or_else(|| format!("Overflow evaluating {} {:?} {}", a, self, b))
+    }
 }
 
-fn operations(input: &str) -> IResult<&str, Vec<Op>> {
-    let (input, ops) = separated_list1(space1, alt((tag("+"), tag("*")))).parse(input)?;
-    Ok((
-        input,
-        ops.iter()
-            .map(|op| match *op {
-                "+" => Op::Add,
-                "*" => Op::Mul,
-                _ => unreachable!(),
-            })
-            .collect(),
-    ))
-}
-
-fn parse_input(input: &str) -> IResult<&str, Problems> {
-    let (input, (nums, _, ops)) = (
-        separated_list1(multispace1, numbers),
-        multispace1,
-        operations,
-    )
-        .parse(input)?;
-
-    Ok((
-        input,
-        Problems {
-            numbers: nums,
-            operations: ops,
-        },
-    ))
-}
-
-fn part_1(problems: &Problems) -> usize {
-    problems
-        .operations
-        .iter()
-        .enumerate()
-        .map(|(idx, op)| {
-            let nums = problems.numbers.iter().map(|row| row[idx]);
-            match op {
-                Op::Add => nums.sum::<usize>(),
-                Op::Mul => nums.product(),
-            }
-        })
-        .sum()
+/// A single problem on the worksheet: an operation applied across a list of operands, left to right.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub operands: Vec<i128>,
+    pub op: Op,
 }
 
-fn part_2(input: &str) -> usize {
-    let lines = input
-        .lines()
-        .map(|line| line.chars().collect_vec())
-        .collect_vec();
+impl Problem {
+    pub fn evaluate(&self) -> Result<i128, String> {
+        let (first, rest) = self
+            .operands
+            .split_first()
+            .ok_or_else(|| format!("No operands for {:?}", self.op))?;
 
-    let num_cols = lines.iter().map(|row| row.len()).max().unwrap();
-    let ops_row_idx = lines.len() - 1;
+        rest.iter()
+            .try_fold(*first, |acc, &n| self.op.apply(acc, n))
+    }
+}
 
-    let mut nums = vec![];
-    let mut total = 0;
+/// One problem's worth of columns on the worksheet, with its operands read both ways.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Block {
+    pub columns: Range<usize>,
+    pub op: Op,
+    row_operands: Vec<i128>,
+    column_operands: Vec<i128>,
+}
 
-    for col_idx in (0..num_cols).rev() {
-        let chars = lines
-            .iter()
-            .map(|row| row.get(col_idx).unwrap_or(&' '))
-            .collect_vec();
+/// The whole worksheet as a character grid, split into problems at the blank columns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Worksheet {
+    pub grid: Grid<char>,
+    pub blocks: Vec<Block>,
+}
 
-        if let Ok(num) = chars[..ops_row_idx].iter().join("").trim().parse::<usize>() {
-            nums.push(num);
-        } else {
-            continue; // Blank column
-        }
+impl Worksheet {
+    /// The problems read row-wise, left to right: each row of a block is one operand.
+    pub fn rows(&self) -> impl Iterator<Item = Problem> + '_ {
+        self.blocks.iter().map(|block| Problem {
+            operands: block.row_operands.clone(),
+            op: block.op,
+        })
+    }
 
-        let op = match chars[ops_row_idx] {
-            '+' => Op::Add,
-            '*' => Op::Mul,
-            _ => continue, // no operation yet
-        };
+    /// The problems read column-wise, right to left: each column of a block is one operand.
+    pub fn columns(&self) -> impl Iterator<Item = Problem> + '_ {
+        self.blocks.iter().rev().map(|block| Problem {
+            operands: block.column_operands.clone(),
+            op: block.op,
+        })
+    }
+}
 
-        match op {
-            Op::Add => {
-                total += nums.iter().sum::<usize>();
-            }
-            Op::Mul => {
-                total += nums.iter().product::<usize>();
-            }
+fn parse_operand(digits: &str, location: impl Fn() -> String) -> Result<i128, String> {
+    let digits = digits.trim();
+    if digits.is_empty() || !digits.chars().all(|ch| ch.is_ascii_digit()) {
+        return Err(format!(
+            "Expected a number {}, found {:?}",
+            location(),
+            digits
+        ));
+    }
+
+    digits
+        .parse()
+        .map_err(|e| format!("Bad number {} {:?}: {}", location(), digits, e))
+}
+
+fn parse_block(grid: &Grid<char>, columns: Range<usize>) -> Result<Block, String> {
+    let ops_row_idx = grid.height() - 1;
+    let cols = || format!("in columns {}-{}", columns.start + 1, columns.end);
+
+    let symbols = columns
+        .clone()
+        .map(|x| grid[(x, ops_row_idx)])
+        .filter(|ch| !ch.is_whitespace())
+        .collect_vec();
+    let op = match symbols[..] {
+        [symbol] => Op::try_from(symbol)?,
+        _ => {
+            return Err(format!(
+                "Expected exactly one operation {}, found {:?}",
+                cols(),
+                symbols.iter().join("")
+            ))
         }
-        nums.clear();
+    };
+
+    let row_operands = (0..ops_row_idx)
+        .map(|y| {
+            let digits = columns.clone().map(|x| grid[(x, y)]).collect::<String>();
+            parse_operand(&digits, || format!("on line {} {}", y + 1, cols()))
+        })
+        .try_collect()?;
+
+    let column_operands = columns
+        .clone()
+        .rev()
+        .map(|x| {
+            let digits = (0..ops_row_idx).map(|y| grid[(x, y)]).collect::<String>();
+            parse_operand(&digits, || format!("in column {}", x + 1))
+        })
+        .try_collect()?;
+
+    Ok(Block {
+        columns,
+        op,
+        row_operands,
+        column_operands,
+    })
+}
+
+pub fn parse_input(input: &str) -> Result<Worksheet, String> {
+    // Lines may have lost their trailing spaces, so square the worksheet up first
+    let width = input
+        .lines()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let padded = input
+        .lines()
+        .map(|line| format!("{:width$}", line, width = width))
+        .join("\n");
+    let grid = Grid::parse(&padded, |ch| ch)?;
+
+    if grid.height() < 2 {
+        return Err("Expected at least one row of numbers and a row of operations".to_string());
     }
 
-    total
+    let blank = grid
+        .columns()
+        .map(|column| column.iter().all(|ch| ch.is_whitespace()))
+        .collect_vec();
+    let blocks = (0..grid.width())
+        .chunk_by(|&x| blank[x])
+        .into_iter()
+        .filter(|(is_blank, _)| !is_blank)
+        .map(|(_, mut xs)| {
+            let start = xs.next().unwrap_or_default();
+            let end = xs.last().unwrap_or(start) + 1;
+            parse_block(&grid, start..end)
+        })
+        .try_collect()?;
+
+    Ok(Worksheet { grid, blocks })
 }
 
-pub fn solve() -> SolverResult {
-    let input = std::fs::read_to_string("inputs/day_06.txt")?;
-    let (_, problems) = parse_input(&input).map_err(|e| e.to_string())?;
+fn grand_total(problems: impl Iterator<Item = Problem>) -> Result<i128, String> {
+    problems.into_iter().try_fold(0i128, |total, problem| {
+        total
+            .checked_add(problem.evaluate()?)
+            .ok_or_else(|| "Overflow adding up the grand total".to_string())
+    })
+}
 
-    println!("Part 1: {}", part_1(&problems));
-    println!("Part 2: {}", part_2(&input));
+pub fn part_1(worksheet: &Worksheet) -> Result<i128, String> {
+    grand_total(worksheet.rows())
+}
 
-    Ok(())
+pub fn part_2(worksheet: &Worksheet) -> Result<i128, String> {
+    grand_total(worksheet.columns())
+}
+
+pub fn solve(part: Part, input: &str, params: &Params) -> AnswerResult {
+    params.only(&[])?;
+    let worksheet = parse_input(input)?;
+
+    Ok(match part {
+        Part::One => part_1(&worksheet)?.into(),
+        Part::Two => part_2(&worksheet)?.into(),
+    })
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use rstest::rstest;
 
     const EXAMPLE: &str = "\
 123 328  51 64
@@ -135,28 +234,101 @@ mod tests {
 
     #[test]
     fn test_parse_example() {
-        let (_, problems) = parse_input(EXAMPLE).unwrap();
+        let worksheet = parse_input(EXAMPLE).unwrap();
+
+        assert_eq!(
+            worksheet
+                .blocks
+                .iter()
+                .map(|b| b.columns.clone())
+                .collect_vec(),
+            vec![0..3, 4..7, 8..11, 12..15]
+        );
         assert_eq!(
-            problems,
-            Problems {
-                numbers: vec![
-                    vec![123, 328, 51, 64],
-                    vec![45, 64, 387, 23],
-                    vec![6, 98, 215, 314],
-                ],
-                operations: vec![Op::Mul, Op::Add, Op::Mul, Op::Add],
-            }
-        )
+            worksheet.rows().collect_vec(),
+            vec![
+                Problem {
+                    operands: vec![123, 45, 6],
+                    op: Op::Mul
+                },
+                Problem {
+                    operands: vec![328, 64, 98],
+                    op: Op::Add
+                },
+                Problem {
+                    operands: vec![51, 387, 215],
+                    op: Op::Mul
+                },
+                Problem {
+                    operands: vec![64, 23, 314],
+                    op: Op::Add
+                },
+            ]
+        );
+        assert_eq!(
+            worksheet.columns().next(),
+            Some(Problem {
+                operands: vec![4, 431, 623],
+                op: Op::Add
+            })
+        );
     }
 
     #[test]
     fn test_part_1_example() {
-        let (_, problems) = parse_input(EXAMPLE).unwrap();
-        assert_eq!(part_1(&problems), 4277556);
+        let worksheet = parse_input(EXAMPLE).unwrap();
+        assert_eq!(part_1(&worksheet), Ok(4277556));
     }
 
     #[test]
     fn test_part_2_example() {
-        assert_eq!(part_2(EXAMPLE), 3263827);
+        let worksheet = parse_input(EXAMPLE).unwrap();
+        assert_eq!(part_2(&worksheet), Ok(3263827));
+    }
+
+    #[rstest]
+    #[case("1 2\n+", "Expected exactly one operation in columns 3-3, found \"\"")]
+    #[case(
+        "12\n+*",
+        "Expected exactly one operation in columns 1-2, found \"+*\""
+    )]
+    #[case("1\n%", "Unknown operation: '%'")]
+    #[case("1x\n+ ", "Expected a number on line 1 in columns 1-2, found \"1x\"")]
+    #[case("1\n \n2\n+", "Expected a number on line 2 in columns 1-1, found \"\"")]
+    #[case("12", "Expected at least one row of numbers and a row of operations")]
+    fn test_malformed_worksheet(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(parse_input(input), Err(expected.to_string()));
+    }
+
+    #[rstest]
+    #[case(Op::Add, vec![1, 2, 3], Ok(6))]
+    #[case(Op::Sub, vec![10, 2, 3], Ok(5))]
+    #[case(Op::Mul, vec![2, 3, 4], Ok(24))]
+    #[case(Op::Div, vec![100, 5, 2], Ok(10))]
+    #[case(Op::Min, vec![5, 2, 9], Ok(2))]
+    #[case(Op::Max, vec![5, 2, 9], Ok(9))]
+    #[case(Op::Pow, vec![2, 3, 2], Ok(64))]
+    #[case(Op::Div, vec![1, 0], Err("Division by zero: 1 / 0".to_string()))]
+    #[case(Op::Pow, vec![10, 100], Err("Overflow evaluating 10 Pow 100".to_string()))]
+    #[case(Op::Pow, vec![2, -1], Err("Negative exponent: 2 ^ -1".to_string()))]
+    #[case(Op::Add, vec![], Err("No operands for Add".to_string()))]
+    fn test_evaluate(
+        #[case] op: Op,
+        #[case] operands: Vec<i128>,
+        #[case] expected: Result<i128, String>,
+    ) {
+        assert_eq!(Problem { operands, op }.evaluate(), expected);
+    }
+
+    #[test]
+    fn test_more_operations() {
+        let worksheet = "\
+12 3
+ 4 5
+-  ^";
+        let worksheet = parse_input(worksheet).unwrap();
+
+        assert_eq!(part_1(&worksheet), Ok(8 + 243));
+        assert_eq!(part_2(&worksheet), Ok(35 + (24 - 1)));
     }
 }
@@ -1,110 +1,240 @@
-use crate::utils::SolverResult;
-use itertools::Itertools;
-use std::collections::{HashMap, HashSet};
-use std::fs::read_to_string;
-
-type Splitters = HashSet<usize>;
-type Tachyons = HashSet<usize>;
-
-fn parse_input(input: &str) -> (Vec<Splitters>, Tachyons) {
-    let mut lines = input.lines();
-
-    let mut tachyons = Tachyons::new();
-    tachyons.insert(lines.next().unwrap().find("S").unwrap());
-
-    let splitters: Vec<Splitters> = lines
-        .map(|line| {
-            line.chars()
-                .enumerate()
-                .filter_map(|(i, ch)| (ch == '^').then_some(i))
-                .collect()
-        })
-        .filter(|s: &Splitters| !s.is_empty())
-        .collect_vec();
+use crate::utils::{AnswerResult, Grid, Params, Part};
+use crate::visualization::{Frame, Rgb, VisualizerResult, DIM, GOLD, GREEN, RED, WHITE};
+use ndarray::ArrayView1;
+use num::{BigUint, Zero};
 
-    (splitters, tachyons)
+/// What a single cell of the manifold does to the beams that reach it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Element {
+    pub symbol: char,
+    /// Whether a new beam starts here, on top of any that arrive from above.
+    pub source: bool,
+    /// The columns the beams carry on down in, relative to this one.
+    /// No offsets at all means the beams are absorbed.
+    pub offsets: &'static [isize],
+    pub colour: Option<Rgb>,
 }
 
-fn advance(current: &Tachyons, splitters: &Splitters) -> (Tachyons, usize) {
-    let mut next = Tachyons::new();
-    let mut splits = 0;
+impl Element {
+    /// Whether a beam that hits this element counts as being split.
+    pub fn splits(&self) -> bool {
+        self.offsets.len() > 1
+    }
+}
+
+pub const EMPTY: Element = Element {
+    symbol: '.',
+    source: false,
+    offsets: &[0],
+    colour: None,
+};
+pub const SOURCE: Element = Element {
+    symbol: 'S',
+    source: true,
+    offsets: &[0],
+    colour: Some(GOLD),
+};
+pub const SPLITTER: Element = Element {
+    symbol: '^',
+    source: false,
+    offsets: &[-1, 1],
+    colour: Some(GREEN),
+};
+pub const ABSORBER: Element = Element {
+    symbol: '#',
+    source: false,
+    offsets: &[],
+    colour: Some(DIM),
+};
+pub const LEFT_MIRROR: Element = Element {
+    symbol: '<',
+    source: false,
+    offsets: &[-1],
+    colour: Some(GREEN),
+};
+pub const RIGHT_MIRROR: Element = Element {
+    symbol: '>',
+    source: false,
+    offsets: &[1],
+    colour: Some(GREEN),
+};
+pub const TRIPLE_SPLITTER: Element = Element {
+    symbol: 'Y',
+    source: false,
+    offsets: &[-1, 0, 1],
+    colour: Some(GREEN),
+};
+pub const WIDE_SPLITTER: Element = Element {
+    symbol: 'V',
+    source: false,
+    offsets: &[-2, 2],
+    colour: Some(GREEN),
+};
+
+/// Every element the simulator knows about.
+pub const ELEMENTS: [Element; 8] = [
+    EMPTY,
+    SOURCE,
+    SPLITTER,
+    ABSORBER,
+    LEFT_MIRROR,
+    RIGHT_MIRROR,
+    TRIPLE_SPLITTER,
+    WIDE_SPLITTER,
+];
 
-    for &pos in current {
-        if splitters.contains(&pos) {
-            // No need to worry about the ends, the grid is always wide enough
-            next.insert(pos - 1);
-            next.insert(pos + 1);
-            splits += 1;
+pub type Manifold = Grid<Element>;
+
+/// The number of timelines with a beam in each column, between two rows of the manifold.
+pub type Beams = Vec<BigUint>;
+
+pub fn parse_input(input: &str) -> Result<Manifold, String> {
+    let symbols = Grid::parse(input, |ch| ch)?;
+
+    if let Some(((x, y), symbol)) = symbols
+        .iter()
+        .find(|(_, &ch)| ELEMENTS.iter().all(|e| e.symbol != ch))
+    {
+        return Err(format!(
+            "Unknown manifold element {:?} at line {}, column {}",
+            symbol,
+            y + 1,
+            x + 1
+        ));
+    }
+
+    Ok(symbols.map(|&ch| {
+        ELEMENTS
+            .into_iter()
+            .find(|e| e.symbol == ch)
+            .unwrap_or(EMPTY)
+    }))
+}
+
+/// The beams coming out of one row of the manifold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Step {
+    beams: Beams,
+    /// How many splitters in the row were hit.
+    splits: usize,
+    /// Timelines whose beam was absorbed or sent off the side of the manifold.
+    ended: BigUint,
+}
+
+fn advance(current: &[BigUint], row: ArrayView1<Element>) -> Step {
+    let mut step = Step {
+        beams: vec![BigUint::zero(); row.len()],
+        splits: 0,
+        ended: BigUint::zero(),
+    };
+
+    for (x, element) in row.iter().enumerate() {
+        let count = if element.source {
+            &current[x] + 1u32
         } else {
-            next.insert(pos);
+            current[x].clone()
+        };
+        if count.is_zero() {
+            continue;
+        }
+
+        if element.splits() {
+            step.splits += 1;
+        }
+        if element.offsets.is_empty() {
+            step.ended += &count;
+        }
+        for &offset in element.offsets {
+            match x.checked_add_signed(offset).filter(|&t| t < row.len()) {
+                Some(t) => step.beams[t] += &count,
+                None => step.ended += &count,
+            }
         }
     }
 
-    (next, splits)
+    step
 }
 
-fn part_1(start: &Tachyons, splitters: &Vec<Splitters>) -> usize {
-    let (_last, splits) =
-        splitters
-            .iter()
-            .fold((start.clone(), 0), |(current, splits), splitters| {
-                let (next, new_splits) = advance(&current, splitters);
-                (next, splits + new_splits)
-            });
-
-    splits
+/// Everything that happens on the way down the manifold.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Summary {
+    /// How many splitters were hit by at least one beam.
+    pub splits: usize,
+    /// How many timelines there are by the end,
+    /// whether their beam leaves the bottom, leaves the sides or is absorbed.
+    pub timelines: BigUint,
 }
 
-fn paths_below(
-    tachyon: usize,
-    depth: usize,
-    splitters: &Vec<Splitters>,
-    memo: &mut HashMap<(usize, usize), usize>,
-) -> usize {
-    if let Some(&cached) = memo.get(&(tachyon, depth)) {
-        return cached;
-    }
+pub fn summarize(manifold: &Manifold) -> Summary {
+    let mut beams = vec![BigUint::zero(); manifold.width()];
+    let mut summary = Summary {
+        splits: 0,
+        timelines: BigUint::zero(),
+    };
 
-    if depth >= splitters.len() {
-        return 1;
+    for row in manifold.rows() {
+        let step = advance(&beams, row);
+        summary.splits += step.splits;
+        summary.timelines += step.ended;
+        beams = step.beams;
     }
+    summary.timelines += beams.into_iter().sum::<BigUint>();
 
-    let current_splitters = &splitters[depth];
-    let result = if current_splitters.contains(&tachyon) {
-        let left_paths = paths_below(tachyon - 1, depth + 1, splitters, memo);
-        let right_paths = paths_below(tachyon + 1, depth + 1, splitters, memo);
-        left_paths + right_paths
-    } else {
-        paths_below(tachyon, depth + 1, splitters, memo)
-    };
+    summary
+}
 
-    memo.insert((tachyon, depth), result);
+pub fn part_1(manifold: &Manifold) -> usize {
+    summarize(manifold).splits
+}
 
-    result
+pub fn part_2(manifold: &Manifold) -> BigUint {
+    summarize(manifold).timelines
 }
 
-fn part_2(start: &Tachyons, splitters: &Vec<Splitters>) -> usize {
-    paths_below(
-        *start.iter().next().unwrap(),
-        0,
-        splitters,
-        &mut HashMap::new(),
-    )
+fn beam_frames(manifold: &Manifold) -> Vec<Frame> {
+    let mut frame = Frame::new(manifold.width(), manifold.height());
+    for ((x, y), element) in manifold.iter() {
+        if let Some(colour) = element.colour {
+            frame.set(x, y, colour);
+        }
+    }
+
+    let mut frames = vec![frame.clone()];
+    let mut beams = vec![BigUint::zero(); manifold.width()];
+    for (y, row) in manifold.rows().enumerate() {
+        if y > 0 {
+            for x in (0..row.len()).filter(|&x| !beams[x].is_zero()) {
+                frame.set(x, y, if row[x].splits() { RED } else { WHITE });
+            }
+            frames.push(frame.clone());
+        }
+        beams = advance(&beams, row).beams;
+    }
+
+    frames
 }
 
-pub fn solve() -> SolverResult {
-    let input = read_to_string("inputs/day_07.txt")?;
-    let (splitters, start) = parse_input(&input);
+pub fn solve(part: Part, input: &str, params: &Params) -> AnswerResult {
+    params.only(&[])?;
+    let manifold = parse_input(input)?;
 
-    println!("Part 1: {}", part_1(&start, &splitters));
-    println!("Part 2: {}", part_2(&start, &splitters));
+    Ok(match part {
+        Part::One => part_1(&manifold).into(),
+        Part::Two => part_2(&manifold).into(),
+    })
+}
 
-    Ok(())
+pub fn visualize(input: &str) -> VisualizerResult {
+    Ok(beam_frames(&parse_input(input)?))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::Answer;
+    use itertools::Itertools;
+    use proptest::prelude::*;
+    use std::collections::HashSet;
 
     const EXAMPLE: &str = "\
 .......S.......
@@ -126,15 +256,165 @@ mod tests {
 
     #[test]
     fn test_part_1_example() {
-        let (splitters, start) = parse_input(EXAMPLE);
+        let manifold = parse_input(EXAMPLE).unwrap();
 
-        assert_eq!(part_1(&start, &splitters), 21);
+        assert_eq!(part_1(&manifold), 21);
     }
 
     #[test]
     fn test_part_2_example() {
-        let (splitters, start) = parse_input(EXAMPLE);
+        let manifold = parse_input(EXAMPLE).unwrap();
+
+        assert_eq!(part_2(&manifold), BigUint::from(40u32));
+    }
+
+    #[test]
+    fn test_splitters_on_the_edges() {
+        // Every pair of rows doubles the beams in the middle column,
+        // and sends just as many off the sides
+        let pairs = 200;
+        let input = std::iter::once(".S.")
+            .chain(std::iter::repeat_n([".^.", "^.^"], pairs).flatten())
+            .join("\n");
+        let manifold = parse_input(&input).unwrap();
+
+        assert_eq!(
+            summarize(&manifold),
+            Summary {
+                splits: 3 * pairs,
+                timelines: BigUint::from(3u32) * BigUint::from(2u32).pow(pairs as u32) - 2u32,
+            }
+        );
+        assert_eq!(
+            solve(Part::Two, &input, &Params::new()).unwrap(),
+            Answer::from(summarize(&manifold).timelines)
+        );
+    }
+
+    #[test]
+    fn test_beam_frames_example() {
+        let manifold = parse_input(EXAMPLE).unwrap();
+        let frames = beam_frames(&manifold);
 
-        assert_eq!(part_2(&start, &splitters), 40);
+        assert_eq!(frames.len(), manifold.height());
+        assert_eq!(frames[0].get(7, 0), Some(GOLD));
+        assert_eq!(frames[0].get(7, 2), Some(GREEN));
+        assert_eq!(frames[1].get(7, 1), Some(WHITE));
+        assert_eq!(frames[2].get(7, 2), Some(RED));
+        assert_eq!(frames[4].get(6, 4), Some(RED));
+    }
+
+    #[test]
+    fn test_other_elements() {
+        let manifold = parse_input("S..S\n.#..\nY..<\n#...").unwrap();
+
+        // One timeline goes off the left, one is absorbed, and two reach the bottom
+        assert_eq!(
+            summarize(&manifold),
+            Summary {
+                splits: 1,
+                timelines: BigUint::from(4u32),
+            }
+        );
+    }
+
+    #[test]
+    fn test_unknown_element() {
+        assert_eq!(
+            parse_input("S.\n.?"),
+            Err("Unknown manifold element '?' at line 2, column 2".to_string())
+        );
+    }
+
+    /// Every path a beam can take from `path`, along with the splitting elements it hit on the way.
+    fn enumerate_paths(
+        path: Vec<usize>,
+        rows: &[ArrayView1<Element>],
+        depth: usize,
+        hit: &mut HashSet<(usize, usize)>,
+    ) -> Vec<Vec<usize>> {
+        let Some((row, rest)) = rows.split_first() else {
+            return vec![path];
+        };
+
+        let tachyon = *path.last().unwrap();
+        let element = row[tachyon];
+        if element.splits() {
+            hit.insert((tachyon, depth));
+        }
+        if element.offsets.is_empty() {
+            return vec![path];
+        }
+
+        element
+            .offsets
+            .iter()
+            .enumerate()
+            .flat_map(|(idx, &offset)| {
+                let mut path = path.clone();
+                match tachyon
+                    .checked_add_signed(offset)
+                    .filter(|&t| t < row.len())
+                {
+                    Some(t) => {
+                        path.push(t);
+                        enumerate_paths(path, rest, depth + 1, hit)
+                    }
+                    // Off the side, so this timeline is done
+                    None => {
+                        path.push(usize::MAX - idx);
+                        vec![path]
+                    }
+                }
+            })
+            .collect()
+    }
+
+    fn manifold_strategy() -> impl Strategy<Value = String> {
+        let symbols = ELEMENTS
+            .iter()
+            .filter(|e| !e.source)
+            .map(|e| e.symbol)
+            .collect_vec();
+
+        (1usize..10).prop_flat_map(move |width| {
+            (
+                prop::collection::vec(any::<bool>(), width),
+                prop::collection::vec(
+                    prop::collection::vec(prop::sample::select(symbols.clone()), width),
+                    0..6,
+                ),
+            )
+                .prop_map(move |(sources, rows)| {
+                    let mut lines = vec![sources
+                        .iter()
+                        .map(|&s| if s { 'S' } else { '.' })
+                        .collect::<String>()];
+                    lines.extend(rows.iter().map(|row| row.iter().collect::<String>()));
+                    lines.join("\n")
+                })
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn test_summary_matches_path_enumeration(manifold in manifold_strategy()) {
+            let manifold = parse_input(&manifold).unwrap();
+            let rows = manifold.rows().collect_vec();
+
+            let mut hit = HashSet::new();
+            let paths: HashSet<Vec<usize>> = (0..manifold.width())
+                .filter(|&x| manifold[(x, 0)].source)
+                .flat_map(|x| enumerate_paths(vec![x], &rows[1..], 1, &mut hit))
+                .collect();
+
+            prop_assert_eq!(
+                summarize(&manifold),
+                Summary {
+                    splits: hit.len(),
+                    timelines: BigUint::from(paths.len()),
+                }
+            );
+        }
     }
 }
@@ -1,109 +1,394 @@
-use crate::utils::{lines1, SolverResult};
+use crate::utils::{AnswerResult, KdTree, Metric, Params, Part, UnionFind};
 use itertools::Itertools;
 use nom::bytes::complete::tag;
-use nom::character::complete::usize;
-use nom::IResult;
-use nom::Parser;
-use std::collections::HashMap;
+use nom::character::complete::i64;
+use nom::combinator::all_consuming;
+use nom::multi::separated_list1;
+use nom::{IResult, Parser};
+use std::collections::BTreeMap;
+use std::error::Error;
 
+/// A junction box, at a point in `N` dimensions.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
-struct Junction {
-    x: usize,
-    y: usize,
-    z: usize,
-}
+pub struct Point<const N: usize>(pub [i64; N]);
 
-fn distance(a: &Junction, b: &Junction) -> f64 {
-    ((a.x.abs_diff(b.x).pow(2) + a.y.abs_diff(b.y).pow(2) + a.z.abs_diff(b.z).pow(2)) as f64).sqrt()
+impl<const N: usize> Point<N> {
+    pub fn x(&self) -> i64 {
+        self.0[0]
+    }
 }
 
-fn junction(input: &str) -> IResult<&str, Junction> {
-    let (input, (x, _, y, _, z)) = (usize, tag(","), usize, tag(","), usize).parse(input)?;
-    Ok((input, Junction { x, y, z }))
+fn coordinates(input: &str) -> IResult<&str, Vec<i64>> {
+    separated_list1(tag(","), i64).parse(input)
 }
 
-fn junctions(input: &str) -> IResult<&str, Vec<Junction>> {
-    lines1(junction).parse(input)
+/// Parse one junction per line, as comma-separated coordinates.
+/// The first line decides how many dimensions there are, and every other line has to agree.
+pub fn parse_input(input: &str) -> Result<Vec<Vec<i64>>, String> {
+    let lines = input
+        .trim()
+        .lines()
+        .enumerate()
+        .map(|(idx, line)| {
+            all_consuming(coordinates)
+                .parse(line.trim())
+                .map(|(_, coordinates)| coordinates)
+                .map_err(|_| {
+                    format!(
+                        "Bad junction on line {}: expected comma-separated integers, got {:?}",
+                        idx + 1,
+                        line.trim()
+                    )
+                })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let dimensions = lines.first().map_or(0, |first| first.len());
+    if let Some((idx, line)) = lines.iter().find_position(|line| line.len() != dimensions) {
+        return Err(format!(
+            "Junction on line {} has {} coordinates, expected {}",
+            idx + 1,
+            line.len(),
+            dimensions
+        ));
+    }
+
+    Ok(lines)
 }
 
-fn part_1(junctions: &Vec<Junction>, num_connections: usize) -> usize {
-    let distances = junctions
+/// Fix the number of dimensions of already-parsed junctions.
+pub fn points<const N: usize>(lines: &[Vec<i64>]) -> Result<Vec<Point<N>>, String> {
+    lines
         .iter()
-        .tuple_combinations()
-        .filter_map(|(a, b)| (a != b).then_some(((a, b), distance(a, b))))
-        .k_smallest_by(num_connections, |&(_, d1), &(_, d2)| d1.total_cmp(&d2))
-        .collect_vec();
-
-    let mut circuits: HashMap<Junction, usize> = junctions
-        .clone()
-        .into_iter()
-        .enumerate()
-        .map(|(j, i)| (i, j))
-        .collect();
+        .map(|line| {
+            <[i64; N]>::try_from(line.as_slice())
+                .map(Point)
+                .map_err(|_| format!("Expected {} coordinates, found {}", N, line.len()))
+        })
+        .collect()
+}
+
+/// Index the junctions for finding the closest pairs under `metric`.
+/// Pairs at the same distance come out ordered by the positions of their junctions in the input,
+/// so they are always connected in the same order.
+fn index<const N: usize>(junctions: &[Point<N>], metric: Metric) -> Result<KdTree<N>, String> {
+    KdTree::with_metric(junctions.iter().map(|p| p.0).collect(), metric)
+}
+
+/// A pair of junctions wired together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Connection {
+    pub distance: u128,
+    pub a: usize,
+    pub b: usize,
+    /// Whether this connection joined two separate circuits.
+    pub merged: bool,
+}
+
+/// When to stop connecting junctions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Until {
+    /// After this many connections, whether or not they merged circuits.
+    Connections(usize),
+    /// As soon as every junction is in the same circuit.
+    Joined,
+    /// As soon as every junction is in the same circuit
+    /// and at least this many connections have been made, so both parts can be read off one run.
+    JoinedAfter(usize),
+}
 
-    for ((a, b), _) in distances {
-        let circuit_a = *circuits.get(a).unwrap();
-        let circuit_b = *circuits.get(b).unwrap();
+/// The junctions wired together so far, closest pairs first.
+#[derive(Debug, Clone)]
+pub struct Circuits {
+    pub connections: Vec<Connection>,
+    pub circuits: UnionFind,
+}
+
+/// How many of the largest circuits to keep track of after each connection.
+const LARGEST: usize = 3;
+
+/// The state of the circuits after some number of connections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snapshot {
+    pub components: usize,
+    /// The sizes of the largest few circuits, largest first.
+    pub largest: Vec<usize>,
+}
+
+/// A single run of Kruskal's algorithm over the junctions:
+/// every connection made in order, and the state of the circuits after each one.
+#[derive(Debug, Clone)]
+pub struct Wiring {
+    pub connections: Vec<Connection>,
+    /// The state before any connections, then after each one.
+    pub timeline: Vec<Snapshot>,
+    /// Whether every pair of junctions has been connected.
+    pub exhausted: bool,
+}
+
+/// Connect junctions, closest pairs first, until `until` (or there are no pairs left).
+pub fn wire<const N: usize>(
+    junctions: &[Point<N>],
+    metric: Metric,
+    until: Until,
+) -> Result<Wiring, String> {
+    let mut circuits = UnionFind::new(junctions.len());
+    // How many circuits there are of each size
+    let mut sizes = BTreeMap::new();
+    if !junctions.is_empty() {
+        sizes.insert(1, junctions.len());
+    }
 
-        for (_, circuit) in circuits.iter_mut() {
-            if *circuit == circuit_b {
-                *circuit = circuit_a;
+    let snapshot = |circuits: &UnionFind, sizes: &BTreeMap<usize, usize>| Snapshot {
+        components: circuits.component_count(),
+        largest: sizes
+            .iter()
+            .rev()
+            .flat_map(|(&size, &count)| std::iter::repeat_n(size, count))
+            .take(LARGEST)
+            .collect(),
+    };
+
+    let mut connections = vec![];
+    let mut timeline = vec![snapshot(&circuits, &sizes)];
+    let index = index(junctions, metric)?;
+    let mut pairs = index.closest_pairs();
+
+    let exhausted = loop {
+        let done = match until {
+            Until::Connections(n) => connections.len() >= n,
+            Until::Joined => circuits.component_count() <= 1,
+            Until::JoinedAfter(n) => circuits.component_count() <= 1 && connections.len() >= n,
+        };
+        if done {
+            break false;
+        }
+        let Some((distance, a, b)) = pairs.next() else {
+            break true;
+        };
+
+        let before = (circuits.component_size(a), circuits.component_size(b));
+        let merged = circuits.union(a, b);
+        if merged {
+            for size in [before.0, before.1] {
+                if let Some(count) = sizes.get_mut(&size) {
+                    *count -= 1;
+                    if *count == 0 {
+                        sizes.remove(&size);
+                    }
+                }
             }
+            *sizes.entry(before.0 + before.1).or_insert(0) += 1;
         }
+
+        connections.push(Connection {
+            distance,
+            a,
+            b,
+            merged,
+        });
+        timeline.push(snapshot(&circuits, &sizes));
+    };
+
+    Ok(Wiring {
+        connections,
+        timeline,
+        exhausted,
+    })
+}
+
+impl Wiring {
+    /// The state of the circuits after the first `n` connections,
+    /// if the wiring got that far (or ran out of pairs before it could).
+    pub fn after(&self, n: usize) -> Option<&Snapshot> {
+        self.timeline
+            .get(n)
+            .or_else(|| self.exhausted.then(|| self.timeline.last()).flatten())
+    }
+
+    /// The index of the connection that put every junction in the same circuit.
+    pub fn joined_at(&self) -> Option<usize> {
+        self.timeline
+            .iter()
+            .position(|snapshot| snapshot.components == 1)
+            .and_then(|n| n.checked_sub(1))
+    }
+
+    /// The connections that merged circuits: a minimum spanning tree,
+    /// or a forest if the junctions never all joined up.
+    pub fn spanning_tree(&self) -> impl Iterator<Item = &Connection> {
+        self.connections.iter().filter(|c| c.merged)
     }
 
-    circuits.values().counts().values().k_largest(3).product()
+    /// Replay the connections up to `until` to recover the circuits at that point.
+    pub fn circuits(&self, junctions: usize, until: Until) -> Circuits {
+        let n = match until {
+            Until::Connections(n) => n,
+            Until::Joined => self
+                .joined_at()
+                .map_or(self.connections.len(), |idx| idx + 1),
+            Until::JoinedAfter(n) => self
+                .joined_at()
+                .map_or(self.connections.len(), |idx| (idx + 1).max(n)),
+        };
+
+        let connections = self.connections.iter().take(n).copied().collect_vec();
+        let mut circuits = UnionFind::new(junctions);
+        for connection in &connections {
+            circuits.union(connection.a, connection.b);
+        }
+
+        Circuits {
+            connections,
+            circuits,
+        }
+    }
 }
 
-fn part_2(junctions: &Vec<Junction>) -> usize {
-    let distances = junctions
-        .iter()
-        .tuple_combinations()
-        .filter_map(|(a, b)| (a != b).then_some(((a, b), distance(a, b))))
-        .sorted_by(|&(_, d1), &(_, d2)| d1.total_cmp(&d2))
-        .collect_vec();
-
-    let mut circuits: HashMap<Junction, usize> = junctions
-        .clone()
-        .into_iter()
-        .enumerate()
-        .map(|(j, i)| (i, j))
-        .collect();
+/// Graphviz colours for the circuits, from the `set312` colour scheme.
+const CLUSTER_COLOURS: usize = 12;
 
-    distances
-        .iter()
-        .fold_while(0, |_, ((a, b), _)| {
-            let circuit_a = *circuits.get(a).unwrap();
-            let circuit_b = *circuits.get(b).unwrap();
+impl Circuits {
+    /// Render the circuits as a Graphviz DOT graph.
+    /// Every circuit with more than one junction is drawn as a coloured cluster,
+    /// and connections that didn't merge anything are dashed.
+    /// Connections are labelled with the squared distance between their junctions, like `d²=9`.
+    pub fn to_dot<const N: usize>(&self, junctions: &[Point<N>]) -> String {
+        let mut lines = vec![
+            "graph circuits {".to_string(),
+            "  node [shape=box, style=filled, fillcolor=white];".to_string(),
+        ];
 
-            for (_, circuit) in circuits.iter_mut() {
-                if *circuit == circuit_b {
-                    *circuit = circuit_a;
-                }
+        let label = |idx: usize| junctions[idx].0.iter().join(",");
+        let groups = self
+            .circuits
+            .groups()
+            .into_iter()
+            .sorted_by_key(|group| std::cmp::Reverse(group.len()))
+            .collect_vec();
+
+        for (cluster, group) in groups.iter().enumerate() {
+            if group.len() == 1 {
+                lines.push(format!("  {} [label=\"{}\"];", group[0], label(group[0])));
+                continue;
             }
 
-            if circuits.values().all_equal() {
-                itertools::FoldWhile::Done(a.x * b.x)
-            } else {
-                itertools::FoldWhile::Continue(0)
+            lines.push(format!("  subgraph cluster_{} {{", cluster));
+            lines.push(format!(
+                "    style=filled; colorscheme=set312; color={};",
+                cluster % CLUSTER_COLOURS + 1
+            ));
+            lines.push(format!("    label=\"{} junctions\";", group.len()));
+            for &idx in group {
+                lines.push(format!("    {} [label=\"{}\"];", idx, label(idx)));
             }
-        })
-        .into_inner()
+            lines.push("  }".to_string());
+        }
+
+        for connection in &self.connections {
+            let style = if connection.merged {
+                ""
+            } else {
+                ", style=dashed"
+            };
+            lines.push(format!(
+                "  {} -- {} [label=\"d²={}\"{}];",
+                connection.a, connection.b, connection.distance, style
+            ));
+        }
+
+        lines.push("}".to_string());
+        lines.join("\n")
+    }
+}
+
+pub fn part_1(wiring: &Wiring, num_connections: usize) -> Result<usize, String> {
+    wiring
+        .after(num_connections)
+        .map(|snapshot| snapshot.largest.iter().product())
+        .ok_or_else(|| format!("Not wired up for {} connections", num_connections))
+}
+
+pub fn part_2<const N: usize>(junctions: &[Point<N>], wiring: &Wiring) -> Result<i128, String> {
+    let idx = wiring.joined_at().ok_or_else(|| {
+        if junctions.len() < 2 {
+            "There have to be at least two junctions to join".to_string()
+        } else {
+            "Not wired up until every junction is joined".to_string()
+        }
+    })?;
+    let last = wiring.connections[idx];
+
+    Ok(junctions[last.a].x() as i128 * junctions[last.b].x() as i128)
+}
+
+/// Call `$f::<N>(...)` with `N` set to the number of dimensions of the parsed junctions,
+/// which can be anywhere from 1 to 8.
+macro_rules! with_dimensions {
+    ($lines:expr, $f:ident($($arg:expr),*)) => {
+        match $lines.first().map_or(0, |first| first.len()) {
+            1 => $f::<1>($($arg),*),
+            2 => $f::<2>($($arg),*),
+            3 => $f::<3>($($arg),*),
+            4 => $f::<4>($($arg),*),
+            5 => $f::<5>($($arg),*),
+            6 => $f::<6>($($arg),*),
+            7 => $f::<7>($($arg),*),
+            8 => $f::<8>($($arg),*),
+            dimensions => Err(format!(
+                "Unsupported number of dimensions: {}, expected 1 to 8",
+                dimensions
+            )
+            .into()),
+        }
+    };
+}
+
+/// How many connections to make for part 1.
+const CONNECTIONS: usize = 1000;
+
+fn solve_in<const N: usize>(part: Part, lines: &[Vec<i64>], connections: usize) -> AnswerResult {
+    let junctions = points::<N>(lines)?;
+    let wiring = wire(
+        &junctions,
+        Metric::SquaredEuclidean,
+        Until::JoinedAfter(connections),
+    )?;
+
+    Ok(match part {
+        Part::One => part_1(&wiring, connections)?.into(),
+        Part::Two => part_2(&junctions, &wiring)?.into(),
+    })
+}
+
+pub fn solve(part: Part, input: &str, params: &Params) -> AnswerResult {
+    params.only(&["connections"])?;
+    let connections = params.get("connections", CONNECTIONS)?;
+    let lines = parse_input(input)?;
+
+    with_dimensions!(lines, solve_in(part, &lines, connections))
 }
 
-pub fn solve() -> SolverResult {
-    let input = std::fs::read_to_string("inputs/day_08.txt")?;
-    let (_, junctions) = junctions(&input).unwrap();
+fn dot_in<const N: usize>(lines: &[Vec<i64>], until: Until) -> Result<String, Box<dyn Error>> {
+    let junctions = points::<N>(lines)?;
+    let wiring = wire(&junctions, Metric::SquaredEuclidean, until)?;
 
-    println!("Part 1: {}", part_1(&junctions, 1000));
-    println!("Part 2: {}", part_2(&junctions));
+    Ok(wiring.circuits(junctions.len(), until).to_dot(&junctions))
+}
+
+/// Connect the junctions in `input` until `until`, and render the result as a Graphviz DOT graph.
+pub fn dot(input: &str, until: Until) -> Result<String, Box<dyn Error>> {
+    let lines = parse_input(input)?;
 
-    Ok(())
+    with_dimensions!(lines, dot_in(&lines, until))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::kd_tree::tests::{metric_strategy, points_strategy};
+    use proptest::prelude::*;
+    use rstest::rstest;
 
     const EXAMPLE: &str = "\
 162,817,812
@@ -127,17 +412,260 @@ mod tests {
 984,92,344
 425,690,689";
 
+    fn example() -> Vec<Point<3>> {
+        points(&parse_input(EXAMPLE).unwrap()).unwrap()
+    }
+
     #[test]
     fn test_part_1_example() {
-        let (_, junctions) = junctions(EXAMPLE).unwrap();
+        let junctions = example();
+        let wiring = wire(&junctions, Metric::SquaredEuclidean, Until::Connections(10)).unwrap();
 
-        assert_eq!(part_1(&junctions, 10), 40);
+        assert_eq!(part_1(&wiring, 10), Ok(40));
     }
 
     #[test]
     fn test_part_2_example() {
-        let (_, junctions) = junctions(EXAMPLE).unwrap();
+        let junctions = example();
+        let wiring = wire(&junctions, Metric::SquaredEuclidean, Until::Joined).unwrap();
+
+        assert_eq!(part_2(&junctions, &wiring), Ok(25272));
+    }
+
+    #[test]
+    fn test_both_parts_from_one_wiring() {
+        let junctions = example();
+        let wiring = wire(&junctions, Metric::SquaredEuclidean, Until::JoinedAfter(10)).unwrap();
 
-        assert_eq!(part_2(&junctions), 25272);
+        assert_eq!(part_1(&wiring, 10), Ok(40));
+        assert_eq!(part_2(&junctions, &wiring), Ok(25272));
+
+        // There are only 190 pairs, so this runs out of them long after everything is joined
+        let wiring = wire(
+            &junctions,
+            Metric::SquaredEuclidean,
+            Until::JoinedAfter(1000),
+        )
+        .unwrap();
+        assert_eq!(part_1(&wiring, 1000), Ok(20));
+        assert_eq!(part_2(&junctions, &wiring), Ok(25272));
+    }
+
+    /// Connect every pair in order of distance, ties broken by index, without any cleverness.
+    fn last_connection_brute_force<const N: usize>(
+        junctions: &[Point<N>],
+        metric: Metric,
+    ) -> Option<i128> {
+        let mut circuits = UnionFind::new(junctions.len());
+
+        (0..junctions.len())
+            .tuple_combinations()
+            .map(|(i, j)| {
+                (
+                    metric.distance(&junctions[i].0, &junctions[j].0).unwrap(),
+                    i,
+                    j,
+                )
+            })
+            .sorted()
+            .find(|&(_, i, j)| circuits.union(i, j) && circuits.component_count() == 1)
+            .map(|(_, i, j)| junctions[i].x() as i128 * junctions[j].x() as i128)
+    }
+
+    #[rstest]
+    #[case(Metric::SquaredEuclidean)]
+    #[case(Metric::Manhattan)]
+    #[case(Metric::Chebyshev)]
+    fn test_part_2_example_matches_brute_force(#[case] metric: Metric) {
+        let junctions = example();
+
+        assert_eq!(
+            part_2(
+                &junctions,
+                &wire(&junctions, metric, Until::Joined).unwrap()
+            )
+            .ok(),
+            last_connection_brute_force(&junctions, metric)
+        );
+    }
+
+    #[test]
+    fn test_other_dimensions() {
+        let flat = "0,0\n-3,4\n10,0\n10,-1";
+        let junctions = points::<2>(&parse_input(flat).unwrap()).unwrap();
+
+        assert_eq!(junctions[1], Point([-3, 4]));
+        let wiring = wire(&junctions, Metric::SquaredEuclidean, Until::Connections(1)).unwrap();
+        assert_eq!(part_1(&wiring, 1), Ok(2));
+        assert!(part_2(&junctions, &wiring).is_err());
+        assert_eq!(
+            solve(Part::Two, flat, &Params::new()).unwrap().to_string(),
+            "0"
+        );
+
+        let deep = "1,0,0,0\n2,0,0,0\n-5,0,0,0";
+        assert_eq!(
+            solve(Part::Two, deep, &Params::new()).unwrap().to_string(),
+            "-5"
+        );
+    }
+
+    #[test]
+    fn test_too_many_dimensions() {
+        assert_eq!(
+            solve(Part::One, "1,2,3,4,5,6,7,8,9", &Params::new())
+                .unwrap_err()
+                .to_string(),
+            "Unsupported number of dimensions: 9, expected 1 to 8"
+        );
+    }
+
+    #[test]
+    fn test_never_joined() {
+        assert_eq!(
+            solve(Part::Two, "1,2,3", &Params::new())
+                .unwrap_err()
+                .to_string(),
+            "There have to be at least two junctions to join"
+        );
+    }
+
+    #[rstest]
+    #[case(
+        "1,2,3\n4,5\n6,7,8",
+        "Junction on line 2 has 2 coordinates, expected 3"
+    )]
+    #[case(
+        "1,2\n3,x",
+        "Bad junction on line 2: expected comma-separated integers, got \"3,x\""
+    )]
+    fn test_bad_input(#[case] input: &str, #[case] expected: &str) {
+        assert_eq!(parse_input(input), Err(expected.to_string()));
+    }
+
+    #[test]
+    fn test_wiring_example() {
+        let junctions = example();
+        let wiring = wire(&junctions, Metric::SquaredEuclidean, Until::Joined).unwrap();
+
+        assert_eq!(
+            wiring.after(10),
+            Some(&Snapshot {
+                components: 11,
+                largest: vec![5, 4, 2],
+            })
+        );
+        assert_eq!(wiring.after(wiring.connections.len() + 1), None);
+        assert_eq!(wiring.spanning_tree().count(), junctions.len() - 1);
+
+        let first = wiring.circuits(junctions.len(), Until::Connections(10));
+        assert_eq!(first.connections.len(), 10);
+        assert_eq!(first.connections.iter().filter(|c| !c.merged).count(), 1);
+        assert_eq!(first.circuits.component_count(), 11);
+
+        let joined = wiring.circuits(junctions.len(), Until::Joined);
+        assert_eq!(joined.circuits.component_count(), 1);
+        assert_eq!(joined.connections.len(), wiring.connections.len());
+    }
+
+    #[test]
+    fn test_not_wired_far_enough() {
+        let junctions = example();
+        let wiring = wire(&junctions, Metric::SquaredEuclidean, Until::Joined).unwrap();
+
+        assert!(part_1(&wiring, 1000).is_err());
+        assert_eq!(
+            part_1(
+                &wire(
+                    &junctions,
+                    Metric::SquaredEuclidean,
+                    Until::Connections(1000)
+                )
+                .unwrap(),
+                1000
+            ),
+            Ok(20)
+        );
+    }
+
+    #[test]
+    fn test_dot() {
+        let dot = dot("0,0\n0,3\n0,4\n10,10", Until::Connections(3)).unwrap();
+
+        assert_eq!(
+            dot,
+            "\
+graph circuits {
+  node [shape=box, style=filled, fillcolor=white];
+  subgraph cluster_0 {
+    style=filled; colorscheme=set312; color=1;
+    label=\"3 junctions\";
+    0 [label=\"0,0\"];
+    1 [label=\"0,3\"];
+    2 [label=\"0,4\"];
+  }
+  3 [label=\"10,10\"];
+  1 -- 2 [label=\"d²=1\"];
+  0 -- 1 [label=\"d²=9\"];
+  0 -- 2 [label=\"d²=16\", style=dashed];
+}"
+        );
+    }
+
+    fn until_strategy() -> impl Strategy<Value = Until> {
+        prop_oneof![
+            Just(Until::Joined),
+            (0usize..400).prop_map(Until::Connections),
+            (0usize..400).prop_map(Until::JoinedAfter),
+        ]
+    }
+
+    proptest! {
+        #[test]
+        fn test_part_2_matches_brute_force(
+            points in points_strategy(),
+            metric in metric_strategy(),
+        ) {
+            let junctions = points.into_iter().map(Point).collect_vec();
+
+            let wiring = wire(&junctions, metric, Until::Joined).unwrap();
+
+            prop_assert_eq!(part_2(&junctions, &wiring).ok(), last_connection_brute_force(&junctions, metric));
+        }
+
+        #[test]
+        fn test_timeline_matches_replay(
+            points in points_strategy(),
+            until in until_strategy(),
+        ) {
+            let junctions = points.into_iter().map(Point).collect_vec();
+            let wiring = wire(&junctions, Metric::SquaredEuclidean, until).unwrap();
+
+            prop_assert_eq!(wiring.timeline.len(), wiring.connections.len() + 1);
+            match until {
+                Until::Connections(n) => prop_assert!(wiring.connections.len() == n || wiring.exhausted),
+                Until::Joined => prop_assert!(wiring.timeline.last().unwrap().components <= 1),
+                Until::JoinedAfter(n) => prop_assert!(
+                    (wiring.connections.len() >= n && wiring.timeline.last().unwrap().components <= 1)
+                        || wiring.exhausted
+                ),
+            }
+            for n in 0..wiring.timeline.len() {
+                let circuits = wiring.circuits(junctions.len(), Until::Connections(n)).circuits;
+                prop_assert_eq!(
+                    &wiring.timeline[n],
+                    &Snapshot {
+                        components: circuits.component_count(),
+                        largest: circuits.component_sizes().into_iter().k_largest(LARGEST).collect(),
+                    }
+                );
+            }
+            if !matches!(until, Until::Connections(_)) || wiring.exhausted {
+                prop_assert_eq!(
+                    wiring.spanning_tree().count(),
+                    junctions.len().saturating_sub(1)
+                );
+            }
+        }
     }
 }
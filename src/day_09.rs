@@ -1,21 +1,11 @@
-use crate::utils::SolverResult;
+use crate::utils::{Answer, AnswerResult, Params, Part};
 
-fn part_1() -> usize {
-    0
-}
-
-fn part_2() -> usize {
-    0
-}
-
-pub fn solve() -> SolverResult {
-    println!("Part 1: {}", part_1());
-    println!("Part 2: {}", part_2());
-
-    Ok(())
+pub fn solve(_part: Part, _input: &str, _params: &Params) -> AnswerResult {
+    Ok(Answer::Unsolved)
 }
 
 #[cfg(test)]
 mod tests {
+    #[allow(unused_imports)]
     use super::*;
 }
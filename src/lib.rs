@@ -11,3 +11,83 @@ pub mod day_10;
 pub mod day_11;
 pub mod day_12;
 pub mod utils;
+pub mod visualization;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+use utils::{AnswerResult, Params, Part};
+
+/// Solve one part of the puzzle for the given day from the puzzle input,
+/// without touching the file system or printing anything.
+pub fn solve_str(day: u8, part: Part, input: &str) -> AnswerResult {
+    solve_str_with_params(day, part, input, &Params::new())
+}
+
+/// Like [`solve_str`], but with some of the day's puzzle parameters overridden.
+pub fn solve_str_with_params(day: u8, part: Part, input: &str, params: &Params) -> AnswerResult {
+    match day {
+        1 => day_01::solve(part, input, params),
+        2 => day_02::solve(part, input, params),
+        3 => day_03::solve(part, input, params),
+        4 => day_04::solve(part, input, params),
+        5 => day_05::solve(part, input, params),
+        6 => day_06::solve(part, input, params),
+        7 => day_07::solve(part, input, params),
+        8 => day_08::solve(part, input, params),
+        9 => day_09::solve(part, input, params),
+        10 => day_10::solve(part, input, params),
+        11 => day_11::solve(part, input, params),
+        12 => day_12::solve(part, input, params),
+        _ => Err(format!("Unknown day: {}", day).into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::Answer;
+
+    #[test]
+    fn test_solve_str() {
+        let input = "L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82";
+
+        assert_eq!(solve_str(1, Part::One, input).unwrap(), Answer::Unsigned(3));
+        assert_eq!(solve_str(1, Part::Two, input).unwrap(), Answer::Unsigned(6));
+    }
+
+    #[test]
+    fn test_solve_str_with_params() {
+        let input = "L68\nL30\nR48\nL5\nR60\nL55\nL1\nL99\nR14\nL82";
+        let params = Params::new().with("dial_size", 100).with("initial", 50);
+
+        assert_eq!(
+            solve_str_with_params(1, Part::Two, input, &params).unwrap(),
+            solve_str(1, Part::Two, input).unwrap()
+        );
+        assert_eq!(
+            solve_str_with_params(1, Part::One, input, &Params::new().with("dial_size", 0))
+                .unwrap_err()
+                .to_string(),
+            "The pointer can't start at 50 on a dial of size 0"
+        );
+        assert_eq!(
+            solve_str_with_params(2, Part::One, "", &Params::new().with("connections", 10))
+                .unwrap_err()
+                .to_string(),
+            "Unknown parameter connections, this day takes none"
+        );
+    }
+
+    #[test]
+    fn test_solve_str_unsolved_day() {
+        let answer = solve_str(12, Part::Two, "").unwrap();
+
+        assert!(!answer.is_solved());
+        assert_eq!(answer.to_string(), "unsolved");
+    }
+
+    #[test]
+    fn test_solve_str_unknown_day() {
+        assert!(solve_str(26, Part::One, "").is_err());
+    }
+}
@@ -4,10 +4,11 @@ extern crate lazy_static;
 use std::collections::HashMap;
 use std::env;
 use std::error::Error;
-use std::fs::{create_dir_all, File};
-use std::io::Write;
+use std::fs::{create_dir_all, read_to_string, File};
+use std::io::{ErrorKind, Write};
+use std::path::PathBuf;
 use std::process::exit;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 
 use clap::{Parser, Subcommand};
 use itertools::Itertools;
@@ -16,10 +17,14 @@ use reqwest::header::{HeaderMap, COOKIE};
 
 use advent_of_code_2025::{
     day_01, day_02, day_03, day_04, day_05, day_06, day_07, day_08, day_09, day_10, day_11, day_12,
-    utils::SolverResult,
+    utils::{parse_answers, AnswerResult, Answers, Params, Part},
+    visualization::{play, write_ppm_frames, VisualizerResult},
 };
 
-type SolverFunction = fn() -> SolverResult;
+#[cfg_attr(test, allow(dead_code))]
+type SolverFunction = fn(Part, &str, &Params) -> AnswerResult;
+#[cfg_attr(test, allow(dead_code))]
+type VisualizerFunction = fn(&str) -> VisualizerResult;
 
 lazy_static! {
     static ref SOLVERS: HashMap<&'static str, SolverFunction> = {
@@ -40,9 +45,28 @@ lazy_static! {
 
         solvers
     };
+    static ref VISUALIZERS: HashMap<&'static str, VisualizerFunction> = {
+        let mut visualizers = HashMap::new();
+
+        visualizers.insert("04", day_04::visualize as VisualizerFunction);
+        visualizers.insert("07", day_07::visualize as VisualizerFunction);
+
+        visualizers
+    };
+}
+
+/// Read the known answers, if there are any.
+fn read_answers() -> Result<Answers, Box<dyn Error>> {
+    match read_to_string("inputs/answers.txt") {
+        Ok(contents) => Ok(parse_answers(&contents)?),
+        Err(e) if e.kind() == ErrorKind::NotFound => Ok(Answers::new()),
+        Err(e) => Err(e.into()),
+    }
 }
 
-fn run_solver(day: &str) -> SolverResult {
+/// Solve both parts for a day, checking them against any known answers,
+/// and return how many of them are still unsolved.
+fn run_solver(day: &str, params: &Params, answers: &Answers) -> Result<usize, Box<dyn Error>> {
     if let Some(solver) = SOLVERS.get(day) {
         println!("★★ Day {} ★★★★★", day);
         println!(
@@ -50,20 +74,105 @@ fn run_solver(day: &str) -> SolverResult {
             day.trim_start_matches('0')
         );
 
-        let before = Instant::now();
+        // Days without an input yet (like puzzles that aren't out) can't be solved, but shouldn't stop the rest
+        let input = match read_to_string(format!("inputs/day_{}.txt", day)) {
+            Ok(input) => input,
+            Err(e) if e.kind() == ErrorKind::NotFound => {
+                println!("☆ No input for day {} yet", day);
+                println!("★★★★★★★★★★★★★★★");
+                return Ok(2);
+            }
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut elapsed = Duration::ZERO;
+        let mut unsolved = 0;
 
-        let result = solver();
+        for part in [Part::One, Part::Two] {
+            let before = Instant::now();
+            let answer = solver(part, &input, params)?;
 
-        println!("★ Elapsed time: {:.2?}", before.elapsed());
+            // Unsolved parts don't count towards the timing or get checked
+            if answer.is_solved() {
+                elapsed += before.elapsed();
+
+                // Overridden parameters change the answers, so there's nothing to check against
+                let expected = day
+                    .parse::<u8>()
+                    .ok()
+                    .filter(|_| *params == Params::new())
+                    .and_then(|day| answers.get(&(day, part)));
+                match expected {
+                    Some(expected) if *expected == answer => {
+                        println!("Part {}: {} ✓", part, answer)
+                    }
+                    Some(expected) => {
+                        println!("Part {}: {} ✗ (expected {})", part, answer, expected)
+                    }
+                    None => println!("Part {}: {}", part, answer),
+                }
+            } else {
+                unsolved += 1;
+                println!("Part {}: ☆ {}", part, answer);
+            }
+        }
+
+        if unsolved < 2 {
+            println!("★ Elapsed time: {:.2?}", elapsed);
+        }
         println!("★★★★★★★★★★★★★★★");
 
-        result
+        Ok(unsolved)
     } else {
         println!("Unknown day: {}", day);
         exit(1)
     }
 }
 
+fn run_visualizer(
+    day: &str,
+    output: Option<PathBuf>,
+    delay: u64,
+    scale: usize,
+) -> Result<(), Box<dyn Error>> {
+    if let Some(visualizer) = VISUALIZERS.get(day) {
+        let input = read_to_string(format!("inputs/day_{}.txt", day))?;
+        let frames = visualizer(&input)?;
+
+        if let Some(dir) = output {
+            write_ppm_frames(&frames, &dir, scale)?;
+            println!(
+                "Wrote {} frames for day {} to {}",
+                frames.len(),
+                day,
+                dir.display()
+            );
+        } else {
+            play(&frames, Duration::from_millis(delay))?;
+        }
+
+        Ok(())
+    } else {
+        println!("No visualization for day: {}", day);
+        exit(1)
+    }
+}
+
+fn run_circuits(connections: Option<usize>, output: Option<PathBuf>) -> Result<(), Box<dyn Error>> {
+    let input = read_to_string("inputs/day_08.txt")?;
+    let until = connections.map_or(day_08::Until::Joined, day_08::Until::Connections);
+    let dot = day_08::dot(&input, until)?;
+
+    if let Some(path) = output {
+        File::create(&path)?.write_all(dot.as_bytes())?;
+        println!("Wrote circuits for day 08 to {}", path.display());
+    } else {
+        println!("{}", dot);
+    }
+
+    Ok(())
+}
+
 fn download_input(day: &str) -> Result<(), Box<dyn Error>> {
     let session = env::var("AOC_SESSION").unwrap();
 
@@ -112,6 +221,32 @@ enum Commands {
     Solve {
         /// The day to solve the puzzle for. Elide to solve all days.
         day: Option<String>,
+        /// Override one of the day's puzzle parameters, like `connections=10`. Can be repeated.
+        #[arg(short, long = "param", value_name = "NAME=VALUE")]
+        params: Vec<String>,
+    },
+    /// Animate the puzzle for a given day in the terminal, or export the frames as PPM images.
+    Visualize {
+        /// The day to visualize.
+        day: String,
+        /// Write the frames to this directory instead of playing them in the terminal.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+        /// Milliseconds to wait between frames when playing in the terminal.
+        #[arg(long, default_value_t = 100)]
+        delay: u64,
+        /// Pixels per grid cell in exported frames.
+        #[arg(long, default_value_t = 4)]
+        scale: usize,
+    },
+    /// Export the day 8 circuits as a Graphviz DOT graph.
+    Circuits {
+        /// Stop after this many connections, instead of as soon as every junction is joined.
+        #[arg(short, long)]
+        connections: Option<usize>,
+        /// Write the graph to this file instead of printing it.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
     },
 }
 
@@ -129,16 +264,34 @@ fn main() -> Result<(), Box<dyn Error>> {
                     .try_for_each(|day| download_input(day))?
             }
         }
-        Commands::Solve { day } => {
-            if let Some(day) = day.map(|d| format!("{:0>2}", d)) {
-                run_solver(&day)?;
+        Commands::Solve { day, params } => {
+            let params = Params::parse(&params)?;
+            let days = if let Some(day) = day.map(|d| format!("{:0>2}", d)) {
+                vec![day]
+            } else if params == Params::new() {
+                SOLVERS.keys().sorted().map(|day| day.to_string()).collect()
             } else {
-                SOLVERS
-                    .keys()
-                    .sorted()
-                    .try_for_each(|day| run_solver(day))?
+                return Err("Parameters can only be given when solving a single day".into());
+            };
+
+            let answers = read_answers()?;
+            let mut unsolved = 0;
+            for day in &days {
+                unsolved += run_solver(day, &params, &answers)?;
             }
+
+            println!("☆ {} of {} stars remaining", unsolved, 2 * days.len());
         }
+        Commands::Visualize {
+            day,
+            output,
+            delay,
+            scale,
+        } => run_visualizer(&format!("{:0>2}", day), output, delay, scale)?,
+        Commands::Circuits {
+            connections,
+            output,
+        } => run_circuits(connections, output)?,
     }
 
     Ok(())
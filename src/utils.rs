@@ -1,11 +1,217 @@
+pub mod grid;
+pub mod intervals;
+pub mod kd_tree;
+pub mod params;
+pub mod union_find;
+
+pub use grid::{Grid, Position};
+pub use intervals::IntervalSet;
+pub use kd_tree::{KdTree, Metric};
+pub use params::Params;
+pub use union_find::UnionFind;
+
 use nom::character::complete::{multispace0, multispace1};
 use nom::error::ParseError;
 use nom::multi::separated_list1;
 use nom::sequence::delimited;
 use nom::Parser;
+use num::{BigInt, BigUint};
+use std::collections::BTreeMap;
 use std::error::Error;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+pub type AnswerResult = Result<Answer, Box<dyn Error>>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Part {
+    One,
+    Two,
+}
+
+impl TryFrom<u8> for Part {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        match value {
+            1 => Ok(Part::One),
+            2 => Ok(Part::Two),
+            _ => Err(format!("Unknown part: {}", value)),
+        }
+    }
+}
+
+impl Display for Part {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Part::One => write!(f, "1"),
+            Part::Two => write!(f, "2"),
+        }
+    }
+}
+
+/// The answer to one part of a puzzle.
+///
+/// Integer answers are equal if they have the same value, whichever variant holds them,
+/// and text is equal to an integer if it's written the same way.
+#[derive(Debug, Clone)]
+pub enum Answer {
+    Unsigned(u128),
+    Signed(i128),
+    /// An integer too large for the other variants.
+    Big(BigInt),
+    Text(String),
+    /// There's no solution for this part yet, so there's no answer to show or check.
+    Unsolved,
+}
+
+impl Answer {
+    pub fn is_solved(&self) -> bool {
+        !matches!(self, Answer::Unsolved)
+    }
+
+    fn as_integer(&self) -> Option<BigInt> {
+        match self {
+            Answer::Unsigned(n) => Some(BigInt::from(*n)),
+            Answer::Signed(n) => Some(BigInt::from(*n)),
+            Answer::Big(n) => Some(n.clone()),
+            Answer::Text(_) | Answer::Unsolved => None,
+        }
+    }
+}
+
+impl PartialEq for Answer {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Answer::Text(a), Answer::Text(b)) => a == b,
+            (Answer::Text(text), n) | (n, Answer::Text(text)) => {
+                n.as_integer().is_some_and(|n| n.to_string() == *text)
+            }
+            (Answer::Unsolved, Answer::Unsolved) => true,
+            _ => self
+                .as_integer()
+                .zip(other.as_integer())
+                .is_some_and(|(a, b)| a == b),
+        }
+    }
+}
+
+impl Eq for Answer {}
+
+impl Display for Answer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Answer::Unsigned(n) => write!(f, "{}", n),
+            Answer::Signed(n) => write!(f, "{}", n),
+            Answer::Big(n) => write!(f, "{}", n),
+            Answer::Text(s) => write!(f, "{}", s),
+            Answer::Unsolved => write!(f, "unsolved"),
+        }
+    }
+}
+
+impl FromStr for Answer {
+    type Err = String;
+
+    /// Read an answer back in, as the smallest integer variant that holds it,
+    /// or as text if it isn't written like an integer would be (such as `0123` or `+5`).
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Err("Empty answer".to_string());
+        }
+
+        let answer = if let Ok(n) = s.parse::<u128>() {
+            Answer::Unsigned(n)
+        } else if let Ok(n) = s.parse::<i128>() {
+            Answer::Signed(n)
+        } else if let Ok(n) = s.parse::<BigInt>() {
+            Answer::Big(n)
+        } else {
+            Answer::Text(s.to_string())
+        };
+
+        Ok(if answer.to_string() == s {
+            answer
+        } else {
+            Answer::Text(s.to_string())
+        })
+    }
+}
 
-pub type SolverResult = Result<(), Box<dyn Error>>;
+macro_rules! impl_answer_from {
+    ($variant:ident, $inner:ty, $($t:ty),+) => {
+        $(
+            impl From<$t> for Answer {
+                fn from(value: $t) -> Self {
+                    Answer::$variant(value as $inner)
+                }
+            }
+        )+
+    };
+}
+
+impl_answer_from!(Unsigned, u128, usize, u64, u128);
+impl_answer_from!(Signed, i128, isize, i64, i128);
+
+impl From<BigUint> for Answer {
+    fn from(value: BigUint) -> Self {
+        Answer::Big(value.into())
+    }
+}
+
+impl From<BigInt> for Answer {
+    fn from(value: BigInt) -> Self {
+        Answer::Big(value)
+    }
+}
+
+impl From<String> for Answer {
+    fn from(value: String) -> Self {
+        Answer::Text(value)
+    }
+}
+
+impl From<&str> for Answer {
+    fn from(value: &str) -> Self {
+        Answer::Text(value.to_string())
+    }
+}
+
+/// Known answers, by day and part.
+pub type Answers = BTreeMap<(u8, Part), Answer>;
+
+/// Parse an answers file, with one `day part answer` line per known answer.
+/// Blank lines and lines starting with `#` are skipped.
+pub fn parse_answers(input: &str) -> Result<Answers, String> {
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty() && !line.trim_start().starts_with('#'))
+        .map(|(idx, line)| {
+            let bad = |e: String| format!("Bad answer on line {}: {}", idx + 1, e);
+
+            let mut fields = line.trim().splitn(3, char::is_whitespace);
+            let (Some(day), Some(part), Some(answer)) =
+                (fields.next(), fields.next(), fields.next())
+            else {
+                return Err(bad(format!(
+                    "expected day, part and answer, got {:?}",
+                    line
+                )));
+            };
+
+            let day = day.parse::<u8>().map_err(|e| bad(e.to_string()))?;
+            let part = part
+                .parse::<u8>()
+                .map_err(|e| e.to_string())
+                .and_then(Part::try_from)
+                .map_err(bad)?;
+
+            Ok(((day, part), answer.parse().map_err(bad)?))
+        })
+        .collect()
+}
 
 pub fn whitespace_surrounded<'a, O, E: ParseError<&'a str>, F>(
     inner: F,
@@ -24,3 +230,64 @@ where
 {
     separated_list1(multispace1, inner)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rstest::rstest;
+
+    #[rstest]
+    #[case("42", Answer::Unsigned(42))]
+    #[case("-42", Answer::Signed(-42))]
+    #[case(
+        "123456789012345678901234567890123456789012",
+        Answer::Big("123456789012345678901234567890123456789012".parse().unwrap())
+    )]
+    #[case(" HELLO ", Answer::Text("HELLO".to_string()))]
+    #[case("0123", Answer::Text("0123".to_string()))]
+    #[case("+5", Answer::Text("+5".to_string()))]
+    fn test_parse_answer(#[case] input: &str, #[case] expected: Answer) {
+        let answer = input.parse::<Answer>().unwrap();
+
+        assert_eq!(answer, expected);
+        assert_eq!(answer.to_string(), input.trim());
+    }
+
+    #[test]
+    fn test_answer_equality() {
+        assert_eq!(Answer::Unsigned(5), Answer::Signed(5));
+        assert_eq!(Answer::from(BigUint::from(5u8)), Answer::Unsigned(5));
+        assert_ne!(Answer::Unsigned(5), Answer::Signed(-5));
+        assert_eq!(Answer::Unsigned(5), Answer::from("5"));
+        assert_eq!(Answer::from("-5"), Answer::Signed(-5));
+        assert_ne!(Answer::Unsigned(123), Answer::from("0123"));
+        assert_ne!(Answer::Unsolved, Answer::from("unsolved"));
+        assert!("".parse::<Answer>().is_err());
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        let answers =
+            parse_answers("# day part answer\n1 1 3\n\n1 2 -6\n12 1 MERRY CHRISTMAS\n").unwrap();
+
+        assert_eq!(answers.len(), 3);
+        assert_eq!(answers[&(1, Part::Two)], Answer::Signed(-6));
+        assert_eq!(answers[&(12, Part::One)], Answer::from("MERRY CHRISTMAS"));
+
+        // Text answers that happen to be digits are still checked against the file
+        let answers = parse_answers(
+            "5 1 0123
+5 2 12345",
+        )
+        .unwrap();
+        assert_eq!(Answer::from("0123"), answers[&(5, Part::One)]);
+        assert_ne!(Answer::from("123"), answers[&(5, Part::One)]);
+        assert_eq!(Answer::from("12345"), answers[&(5, Part::Two)]);
+
+        assert_eq!(
+            parse_answers("1 3 5"),
+            Err("Bad answer on line 1: Unknown part: 3".to_string())
+        );
+        assert!(parse_answers("1 1").is_err());
+    }
+}
@@ -0,0 +1,280 @@
+use itertools::Itertools;
+use ndarray::{Array2, ArrayView1};
+use std::fmt::{Display, Formatter};
+use std::ops::{Index, IndexMut};
+
+/// A position in a grid, as `(x, y)` with `(0, 0)` in the top left.
+pub type Position = (usize, usize);
+
+const ORTHOGONAL: [(isize, isize); 4] = [(0, -1), (-1, 0), (1, 0), (0, 1)];
+
+const SURROUNDING: [(isize, isize); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+/// A dense rectangular grid of cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Grid<T> {
+    cells: Array2<T>,
+}
+
+impl<T> Grid<T> {
+    /// Parse character art into a grid, mapping each character to a cell.
+    /// Empty lines before and after the grid are skipped, and every other line must be the same width.
+    pub fn parse<F>(input: &str, mut f: F) -> Result<Self, String>
+    where
+        F: FnMut(char) -> T,
+    {
+        let lines = input.lines().collect_vec();
+        let start = lines
+            .iter()
+            .position(|line| !line.is_empty())
+            .unwrap_or(lines.len());
+        let end = lines
+            .iter()
+            .rposition(|line| !line.is_empty())
+            .map_or(start, |idx| idx + 1);
+        let lines = &lines[start..end];
+        let width = lines.first().map_or(0, |line| line.chars().count());
+
+        let mut cells = Vec::with_capacity(width * lines.len());
+        for (y, line) in lines.iter().enumerate() {
+            let before = cells.len();
+            cells.extend(line.chars().map(&mut f));
+            if cells.len() - before != width {
+                return Err(format!(
+                    "Line {} has width {}, expected {}",
+                    start + y + 1,
+                    cells.len() - before,
+                    width
+                ));
+            }
+        }
+
+        Ok(Grid {
+            cells: Array2::from_shape_vec((lines.len(), width), cells)
+                .map_err(|e| e.to_string())?,
+        })
+    }
+
+    pub fn width(&self) -> usize {
+        self.cells.ncols()
+    }
+
+    pub fn height(&self) -> usize {
+        self.cells.nrows()
+    }
+
+    pub fn contains(&self, (x, y): Position) -> bool {
+        x < self.width() && y < self.height()
+    }
+
+    pub fn get(&self, (x, y): Position) -> Option<&T> {
+        self.cells.get((y, x))
+    }
+
+    pub fn get_mut(&mut self, (x, y): Position) -> Option<&mut T> {
+        self.cells.get_mut((y, x))
+    }
+
+    /// Iterate over every position in row-major order.
+    pub fn positions(&self) -> impl Iterator<Item = Position> {
+        (0..self.height())
+            .cartesian_product(0..self.width())
+            .map(|(y, x)| (x, y))
+    }
+
+    /// Iterate over every cell and its position in row-major order.
+    pub fn iter(&self) -> impl Iterator<Item = (Position, &T)> {
+        self.cells
+            .indexed_iter()
+            .map(|((y, x), cell)| ((x, y), cell))
+    }
+
+    /// The positions orthogonally adjacent to `position` that are inside the grid.
+    pub fn neighbours4(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        self.offset_positions(position, &ORTHOGONAL)
+    }
+
+    /// The positions orthogonally or diagonally adjacent to `position` that are inside the grid.
+    pub fn neighbours8(&self, position: Position) -> impl Iterator<Item = Position> + '_ {
+        self.offset_positions(position, &SURROUNDING)
+    }
+
+    fn offset_positions<'a>(
+        &'a self,
+        position: Position,
+        offsets: &'a [(isize, isize)],
+    ) -> impl Iterator<Item = Position> + 'a {
+        offsets
+            .iter()
+            .filter_map(move |&offset| self.offset(position, offset))
+    }
+
+    /// The position `(dx, dy)` away from `(x, y)`, if it's inside the grid.
+    pub fn offset(&self, (x, y): Position, (dx, dy): (isize, isize)) -> Option<Position> {
+        let moved = (x.checked_add_signed(dx)?, y.checked_add_signed(dy)?);
+        self.contains(moved).then_some(moved)
+    }
+
+    /// The position `(dx, dy)` away from `(x, y)`, wrapping around the edges of the grid.
+    pub fn wrapping_offset(&self, (x, y): Position, (dx, dy): (isize, isize)) -> Position {
+        let wrap =
+            |v: usize, dv: isize, size: usize| (v as isize + dv).rem_euclid(size as isize) as usize;
+        (wrap(x, dx, self.width()), wrap(y, dy, self.height()))
+    }
+
+    pub fn row(&self, y: usize) -> ArrayView1<'_, T> {
+        self.cells.row(y)
+    }
+
+    pub fn column(&self, x: usize) -> ArrayView1<'_, T> {
+        self.cells.column(x)
+    }
+
+    /// Iterate over the rows from top to bottom.
+    pub fn rows(&self) -> impl Iterator<Item = ArrayView1<'_, T>> {
+        self.cells.rows().into_iter()
+    }
+
+    /// Iterate over the columns from left to right.
+    pub fn columns(&self) -> impl Iterator<Item = ArrayView1<'_, T>> {
+        self.cells.columns().into_iter()
+    }
+
+    pub fn map<U, F>(&self, f: F) -> Grid<U>
+    where
+        F: FnMut(&T) -> U,
+    {
+        Grid {
+            cells: self.cells.map(f),
+        }
+    }
+
+    /// Draw the grid as character art, one line per row.
+    pub fn render<F>(&self, mut f: F) -> String
+    where
+        F: FnMut(&T) -> char,
+    {
+        self.rows()
+            .map(|row| row.iter().map(&mut f).collect::<String>())
+            .join("\n")
+    }
+}
+
+impl<T> Index<Position> for Grid<T> {
+    type Output = T;
+
+    fn index(&self, (x, y): Position) -> &Self::Output {
+        &self.cells[(y, x)]
+    }
+}
+
+impl<T> IndexMut<Position> for Grid<T> {
+    fn index_mut(&mut self, (x, y): Position) -> &mut Self::Output {
+        &mut self.cells[(y, x)]
+    }
+}
+
+impl<T: Display> Display for Grid<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (y, row) in self.rows().enumerate() {
+            if y > 0 {
+                writeln!(f)?;
+            }
+            for cell in row {
+                write!(f, "{}", cell)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "\
+.#.
+##.";
+
+    fn example() -> Grid<bool> {
+        Grid::parse(EXAMPLE, |ch| ch == '#').unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let grid = example();
+
+        assert_eq!(grid.width(), 3);
+        assert_eq!(grid.height(), 2);
+        assert!(grid[(1, 0)]);
+        assert!(!grid[(2, 1)]);
+        assert_eq!(grid.get((3, 0)), None);
+    }
+
+    #[test]
+    fn test_parse_ragged() {
+        assert!(Grid::parse(".#.\n#.", |ch| ch).is_err());
+        assert_eq!(
+            Grid::parse("\n.#.\n\n#..\n", |ch| ch).unwrap_err(),
+            "Line 3 has width 0, expected 3"
+        );
+    }
+
+    #[test]
+    fn test_parse_surrounding_blank_lines() {
+        let grid = Grid::parse(&format!("\n\n{}\n\n", EXAMPLE), |ch| ch == '#').unwrap();
+
+        assert_eq!(grid, example());
+        assert_eq!(Grid::parse("\n\n", |ch| ch).unwrap().height(), 0);
+    }
+
+    #[test]
+    fn test_neighbours() {
+        let grid = example();
+
+        assert_eq!(grid.neighbours4((0, 0)).collect_vec(), vec![(1, 0), (0, 1)]);
+        assert_eq!(
+            grid.neighbours8((0, 0)).collect_vec(),
+            vec![(1, 0), (0, 1), (1, 1)]
+        );
+        assert_eq!(grid.neighbours8((1, 1)).count(), 5);
+    }
+
+    #[test]
+    fn test_offsets() {
+        let grid = example();
+
+        assert_eq!(grid.offset((0, 0), (2, 1)), Some((2, 1)));
+        assert_eq!(grid.offset((0, 0), (-1, 0)), None);
+        assert_eq!(grid.wrapping_offset((0, 0), (-1, 0)), (2, 0));
+        assert_eq!(grid.wrapping_offset((2, 1), (4, 3)), (0, 0));
+    }
+
+    #[test]
+    fn test_rows_and_columns() {
+        let grid = example();
+
+        assert_eq!(grid.row(1).to_vec(), vec![true, true, false]);
+        assert_eq!(grid.column(0).to_vec(), vec![false, true]);
+        assert_eq!(grid.rows().count(), 2);
+        assert_eq!(grid.columns().count(), 3);
+    }
+
+    #[test]
+    fn test_render() {
+        let grid = example();
+
+        assert_eq!(grid.render(|&b| if b { '#' } else { '.' }), EXAMPLE);
+        assert_eq!(grid.map(|&b| b as u8).to_string(), "010\n110");
+    }
+}
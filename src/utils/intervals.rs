@@ -0,0 +1,296 @@
+use nom::bytes::complete::tag;
+use nom::character::complete::usize;
+use nom::sequence::separated_pair;
+use nom::{IResult, Parser};
+use std::ops::RangeInclusive;
+
+/// Parse an inclusive range written as `start-end`.
+pub fn range(input: &str) -> IResult<&str, RangeInclusive<usize>> {
+    let (input, (start, end)) = separated_pair(usize, tag("-"), usize).parse(input)?;
+
+    Ok((input, start..=end))
+}
+
+/// A set of integers stored as sorted, disjoint, non-adjacent inclusive ranges.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntervalSet {
+    intervals: Vec<RangeInclusive<usize>>,
+}
+
+impl IntervalSet {
+    pub fn new() -> Self {
+        IntervalSet::default()
+    }
+
+    /// Add a range to the set, merging it with any ranges it overlaps or touches.
+    pub fn insert(&mut self, range: RangeInclusive<usize>) {
+        if range.is_empty() {
+            return;
+        }
+        let (start, end) = range.into_inner();
+
+        // The first interval that ends at or after the one just before `start`...
+        let lo = self
+            .intervals
+            .partition_point(|i| i.end().saturating_add(1) < start);
+        // ... up to the first interval that starts after the one just after `end`
+        let hi = self
+            .intervals
+            .partition_point(|i| *i.start() <= end.saturating_add(1));
+
+        if lo < hi {
+            let merged_start = start.min(*self.intervals[lo].start());
+            let merged_end = end.max(*self.intervals[hi - 1].end());
+            self.intervals.splice(lo..hi, [merged_start..=merged_end]);
+        } else {
+            self.intervals.insert(lo, start..=end);
+        }
+    }
+
+    /// The index of the interval that contains `value`, if any.
+    fn find(&self, value: usize) -> Option<usize> {
+        let idx = self.intervals.partition_point(|i| *i.end() < value);
+        self.intervals
+            .get(idx)
+            .and_then(|i| i.contains(&value).then_some(idx))
+    }
+
+    pub fn contains(&self, value: usize) -> bool {
+        self.find(value).is_some()
+    }
+
+    /// How far `value` is from the nearest value in the set, or `None` if the set is empty.
+    pub fn distance_to(&self, value: usize) -> Option<usize> {
+        let idx = self.intervals.partition_point(|i| *i.end() < value);
+
+        // `value` is either inside the interval at `idx`, before it, or after the one before that
+        let after = self
+            .intervals
+            .get(idx)
+            .map(|i| i.start().saturating_sub(value));
+        let before = idx
+            .checked_sub(1)
+            .map(|prev| value - *self.intervals[prev].end());
+
+        after.into_iter().chain(before).min()
+    }
+
+    /// Whether every value in `range` is in the set.
+    pub fn contains_range(&self, range: &RangeInclusive<usize>) -> bool {
+        range.is_empty()
+            || self
+                .find(*range.start())
+                .is_some_and(|idx| self.intervals[idx].end() >= range.end())
+    }
+
+    pub fn union(&self, other: &IntervalSet) -> IntervalSet {
+        let mut union = self.clone();
+        for range in other.iter() {
+            union.insert(range.clone());
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = vec![];
+        let (mut a, mut b) = (self.iter().peekable(), other.iter().peekable());
+
+        while let (Some(x), Some(y)) = (a.peek(), b.peek()) {
+            let start = *x.start().max(y.start());
+            let end = *x.end().min(y.end());
+            if start <= end {
+                intervals.push(start..=end);
+            }
+
+            // Whichever interval ends first can't overlap anything else on the other side
+            if x.end() < y.end() {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    pub fn difference(&self, other: &IntervalSet) -> IntervalSet {
+        let mut intervals = vec![];
+        let mut others = other.iter().peekable();
+
+        for range in self.iter() {
+            let mut start = *range.start();
+            let end = *range.end();
+
+            // Skip past the subtracted intervals that end before this one starts
+            while others.next_if(|o| *o.end() < start).is_some() {}
+
+            let mut remaining = true;
+            while let Some(o) = others.peek() {
+                if *o.start() > end {
+                    break;
+                }
+                if *o.start() > start {
+                    intervals.push(start..=*o.start() - 1);
+                }
+                if *o.end() >= end {
+                    remaining = false;
+                    break;
+                }
+                start = *o.end() + 1;
+                others.next();
+            }
+
+            if remaining {
+                intervals.push(start..=end);
+            }
+        }
+
+        IntervalSet { intervals }
+    }
+
+    /// The number of values in the set.
+    pub fn total_length(&self) -> u128 {
+        self.intervals
+            .iter()
+            .map(|i| (*i.end() - *i.start()) as u128 + 1)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Iterate over the merged ranges in increasing order.
+    pub fn iter(&self) -> impl Iterator<Item = &RangeInclusive<usize>> {
+        self.intervals.iter()
+    }
+}
+
+impl FromIterator<RangeInclusive<usize>> for IntervalSet {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<usize>>>(iter: I) -> Self {
+        let mut set = IntervalSet::new();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use proptest::prelude::*;
+    use std::collections::BTreeSet;
+
+    fn set(ranges: &[RangeInclusive<usize>]) -> IntervalSet {
+        ranges.iter().cloned().collect()
+    }
+
+    #[test]
+    fn test_range() {
+        assert_eq!(range("3-5"), Ok(("", 3..=5)));
+    }
+
+    #[test]
+    fn test_insert_merges_overlapping_and_adjacent() {
+        let set = set(&[10..=14, 3..=5, 16..=20, 12..=18, 6..=8]);
+
+        assert_eq!(set.iter().cloned().collect_vec(), vec![3..=8, 10..=20]);
+        assert_eq!(set.total_length(), 17);
+    }
+
+    #[test]
+    fn test_insert_at_the_edges_of_usize() {
+        let set = set(&[usize::MAX - 1..=usize::MAX, 0..=1, 2..=2]);
+
+        assert_eq!(
+            set.iter().cloned().collect_vec(),
+            vec![0..=2, usize::MAX - 1..=usize::MAX]
+        );
+        assert!(set.contains(usize::MAX));
+    }
+
+    #[test]
+    fn test_total_length_of_everything() {
+        assert_eq!(
+            set(&[0..=usize::MAX]).total_length(),
+            usize::MAX as u128 + 1
+        );
+    }
+
+    #[test]
+    fn test_contains_range() {
+        let set = set(&[3..=8, 10..=20]);
+
+        assert!(set.contains_range(&(4..=8)));
+        assert!(!set.contains_range(&(8..=10)));
+        assert!(!set.contains_range(&(0..=1)));
+    }
+
+    #[test]
+    fn test_distance_to() {
+        let set = set(&[3..=8, 20..=30]);
+
+        assert_eq!(set.distance_to(5), Some(0));
+        assert_eq!(set.distance_to(0), Some(3));
+        assert_eq!(set.distance_to(12), Some(4));
+        assert_eq!(set.distance_to(15), Some(5));
+        assert_eq!(set.distance_to(usize::MAX), Some(usize::MAX - 30));
+        assert_eq!(IntervalSet::new().distance_to(5), None);
+    }
+
+    fn ranges_strategy() -> impl Strategy<Value = Vec<RangeInclusive<usize>>> {
+        prop::collection::vec(
+            (0usize..100, 0usize..10).prop_map(|(start, len)| start..=start + len),
+            0..15,
+        )
+    }
+
+    fn members(ranges: &[RangeInclusive<usize>]) -> BTreeSet<usize> {
+        ranges.iter().flat_map(|r| r.clone()).collect()
+    }
+
+    fn members_of(set: &IntervalSet) -> BTreeSet<usize> {
+        set.iter().flat_map(|r| r.clone()).collect()
+    }
+
+    proptest! {
+        #[test]
+        fn test_insert_matches_set_membership(ranges in ranges_strategy()) {
+            let set = set(&ranges);
+            let expected = members(&ranges);
+
+            prop_assert_eq!(set.total_length(), expected.len() as u128);
+            for value in 0..120 {
+                prop_assert_eq!(set.contains(value), expected.contains(&value));
+                prop_assert_eq!(
+                    set.distance_to(value),
+                    expected.iter().map(|e| e.abs_diff(value)).min()
+                );
+            }
+            for (a, b) in set.iter().tuple_windows() {
+                prop_assert!(*a.end() + 1 < *b.start());
+            }
+        }
+
+        #[test]
+        fn test_set_operations_match_set_membership(a in ranges_strategy(), b in ranges_strategy()) {
+            let (set_a, set_b) = (set(&a), set(&b));
+            let (members_a, members_b) = (members(&a), members(&b));
+
+            prop_assert_eq!(
+                members_of(&set_a.union(&set_b)),
+                members_a.union(&members_b).copied().collect::<BTreeSet<_>>()
+            );
+            prop_assert_eq!(
+                members_of(&set_a.intersection(&set_b)),
+                members_a.intersection(&members_b).copied().collect::<BTreeSet<_>>()
+            );
+            prop_assert_eq!(
+                members_of(&set_a.difference(&set_b)),
+                members_a.difference(&members_b).copied().collect::<BTreeSet<_>>()
+            );
+        }
+    }
+}
@@ -0,0 +1,311 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+/// A way of measuring the distance between two points, in exact integer arithmetic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// The square of the straight-line distance, which orders pairs the same way.
+    #[default]
+    SquaredEuclidean,
+    /// The sum of the distances along each axis.
+    Manhattan,
+    /// The largest of the distances along each axis.
+    Chebyshev,
+}
+
+impl Metric {
+    /// The distance between `a` and `b`, or `None` if it's too large for a `u128`.
+    pub fn distance<const N: usize>(&self, a: &[i64; N], b: &[i64; N]) -> Option<u128> {
+        let mut diffs = a.iter().zip(b).map(|(p, q)| p.abs_diff(*q) as u128);
+
+        match self {
+            Metric::SquaredEuclidean => {
+                diffs.try_fold(0u128, |total, d| total.checked_add(d.pow(2)))
+            }
+            Metric::Manhattan => diffs.try_fold(0u128, |total, d| total.checked_add(d)),
+            Metric::Chebyshev => Some(diffs.max().unwrap_or(0)),
+        }
+    }
+
+    /// The closest two points can be if they are `diff` apart along one axis.
+    fn axis_distance(&self, diff: u128) -> u128 {
+        match self {
+            Metric::SquaredEuclidean => diff.pow(2),
+            Metric::Manhattan | Metric::Chebyshev => diff,
+        }
+    }
+}
+
+/// A static k-d tree over a list of points, stored implicitly:
+/// each slice of `order` has its splitting point at the middle,
+/// with the points before it on the low side of the split and the points after it on the high side.
+#[derive(Debug, Clone)]
+pub struct KdTree<const N: usize> {
+    points: Vec<[i64; N]>,
+    order: Vec<usize>,
+    metric: Metric,
+}
+
+impl<const N: usize> KdTree<N> {
+    pub fn new(points: Vec<[i64; N]>) -> Result<Self, String> {
+        KdTree::with_metric(points, Metric::default())
+    }
+
+    /// Build a tree over `points`, as long as every distance between them fits in a `u128`.
+    pub fn with_metric(points: Vec<[i64; N]>, metric: Metric) -> Result<Self, String> {
+        // No two points are further apart along any axis than the corners of the bounding box
+        let mut low = [0; N];
+        let mut high = [0; N];
+        for axis in 0..N {
+            low[axis] = points.iter().map(|p| p[axis]).min().unwrap_or(0);
+            high[axis] = points.iter().map(|p| p[axis]).max().unwrap_or(0);
+        }
+        if metric.distance(&low, &high).is_none() {
+            return Err(format!(
+                "The points are too far apart to measure with {:?} distances",
+                metric
+            ));
+        }
+
+        let mut order = (0..points.len()).collect::<Vec<_>>();
+        build(&points, &mut order, 0);
+
+        Ok(KdTree {
+            points,
+            order,
+            metric,
+        })
+    }
+
+    fn distance(&self, a: usize, b: usize) -> u128 {
+        self.metric
+            .distance(&self.points[a], &self.points[b])
+            .expect("distances were checked when the tree was built")
+    }
+
+    pub fn metric(&self) -> Metric {
+        self.metric
+    }
+
+    pub fn len(&self) -> usize {
+        self.points.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.points.is_empty()
+    }
+
+    pub fn point(&self, idx: usize) -> &[i64; N] {
+        &self.points[idx]
+    }
+
+    /// The `k` points nearest to the point at index `query`, not counting itself,
+    /// as `(distance, index)` pairs sorted nearest first, with ties broken by index.
+    pub fn nearest(&self, query: usize, k: usize) -> Vec<(u128, usize)> {
+        let mut best = BinaryHeap::with_capacity(k + 1);
+        if k > 0 {
+            self.search(&self.order, 0, query, k, &mut best);
+        }
+
+        best.into_sorted_vec()
+    }
+
+    fn search(
+        &self,
+        order: &[usize],
+        depth: usize,
+        query: usize,
+        k: usize,
+        best: &mut BinaryHeap<(u128, usize)>,
+    ) {
+        if order.is_empty() {
+            return;
+        }
+
+        let mid = order.len() / 2;
+        let idx = order[mid];
+        let target = &self.points[query];
+
+        if idx != query {
+            let candidate = (self.distance(query, idx), idx);
+            if best.len() < k {
+                best.push(candidate);
+            } else if best.peek().is_some_and(|worst| candidate < *worst) {
+                best.pop();
+                best.push(candidate);
+            }
+        }
+
+        let axis = depth % N;
+        let diff = target[axis] as i128 - self.points[idx][axis] as i128;
+        let (near, far) = if diff < 0 {
+            (&order[..mid], &order[mid + 1..])
+        } else {
+            (&order[mid + 1..], &order[..mid])
+        };
+
+        self.search(near, depth + 1, query, k, best);
+
+        // Anything on the far side is at least as far away as the splitting plane.
+        // Ties still have to be visited, since they might win on index.
+        let plane = self.metric.axis_distance(diff.unsigned_abs());
+        if best.len() < k || best.peek().is_some_and(|&(worst, _)| plane <= worst) {
+            self.search(far, depth + 1, query, k, best);
+        }
+    }
+
+    /// Lazily yield every pair of distinct points as `(distance, i, j)` with `i < j`,
+    /// closest first, with ties broken by `(i, j)`.
+    pub fn closest_pairs(&self) -> ClosestPairs<'_, N> {
+        let mut pairs = ClosestPairs {
+            tree: self,
+            neighbours: vec![vec![]; self.len()],
+            cursors: vec![0; self.len()],
+            candidates: BinaryHeap::new(),
+        };
+
+        for idx in 0..self.len() {
+            pairs.push_next_candidate(idx);
+        }
+
+        pairs
+    }
+}
+
+fn build<const N: usize>(points: &[[i64; N]], order: &mut [usize], depth: usize) {
+    if order.len() <= 1 {
+        return;
+    }
+
+    let axis = depth % N;
+    let mid = order.len() / 2;
+    order.select_nth_unstable_by_key(mid, |&idx| points[idx][axis]);
+
+    let (low, high) = order.split_at_mut(mid);
+    build(points, low, depth + 1);
+    build(points, &mut high[1..], depth + 1);
+}
+
+/// An iterator over the pairs of points in a [`KdTree`], closest first.
+///
+/// Each point walks through its own neighbours in order of distance,
+/// fetching more from the tree as needed, and a heap merges those walks together.
+/// Every pair shows up in the walks of both of its points, so it's only yielded from the lower index.
+pub struct ClosestPairs<'a, const N: usize> {
+    tree: &'a KdTree<N>,
+    neighbours: Vec<Vec<(u128, usize)>>,
+    cursors: Vec<usize>,
+    candidates: BinaryHeap<Reverse<(u128, usize, usize, usize)>>,
+}
+
+impl<const N: usize> ClosestPairs<'_, N> {
+    fn push_next_candidate(&mut self, idx: usize) {
+        let cursor = self.cursors[idx];
+        let available = self.neighbours[idx].len();
+
+        if cursor == available {
+            // Nothing left to fetch once every other point has been seen
+            if available == self.tree.len().saturating_sub(1) && available > 0 {
+                return;
+            }
+            self.neighbours[idx] = self.tree.nearest(idx, (available * 2).max(4));
+            if cursor == self.neighbours[idx].len() {
+                return;
+            }
+        }
+
+        let (distance, other) = self.neighbours[idx][cursor];
+        self.cursors[idx] += 1;
+        self.candidates
+            .push(Reverse((distance, idx.min(other), idx.max(other), idx)));
+    }
+}
+
+impl<const N: usize> Iterator for ClosestPairs<'_, N> {
+    type Item = (u128, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let Reverse((distance, i, j, from)) = self.candidates.pop()?;
+            self.push_next_candidate(from);
+
+            if from == i {
+                return Some((distance, i, j));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use itertools::Itertools;
+    use proptest::prelude::*;
+
+    pub(crate) fn metric_strategy() -> impl Strategy<Value = Metric> {
+        prop::sample::select(vec![
+            Metric::SquaredEuclidean,
+            Metric::Manhattan,
+            Metric::Chebyshev,
+        ])
+    }
+
+    #[test]
+    fn test_metrics() {
+        let (a, b) = ([1, -2, 3], [4, 2, 3]);
+
+        assert_eq!(Metric::SquaredEuclidean.distance(&a, &b), Some(25));
+        assert_eq!(Metric::Manhattan.distance(&a, &b), Some(7));
+        assert_eq!(Metric::Chebyshev.distance(&a, &b), Some(4));
+        assert_eq!(
+            Metric::SquaredEuclidean.distance(&[i64::MIN], &[i64::MAX]),
+            Some((u64::MAX as u128).pow(2))
+        );
+    }
+
+    #[test]
+    fn test_distances_too_large() {
+        let (low, high) = ([i64::MIN, i64::MIN], [i64::MAX, i64::MAX]);
+
+        assert_eq!(Metric::SquaredEuclidean.distance(&low, &high), None);
+        assert!(KdTree::with_metric(vec![low, high], Metric::SquaredEuclidean).is_err());
+        assert!(KdTree::with_metric(vec![low, high], Metric::Manhattan).is_ok());
+        assert!(KdTree::with_metric(vec![low, high], Metric::Chebyshev).is_ok());
+    }
+
+    pub(crate) fn points_strategy() -> impl Strategy<Value = Vec<[i64; 3]>> {
+        // A small coordinate range, so that there are plenty of ties
+        prop::collection::vec(prop::array::uniform3(-5i64..5), 0..40)
+    }
+
+    proptest! {
+        #[test]
+        fn test_nearest_matches_brute_force(points in points_strategy(), k in 0usize..10, metric in metric_strategy()) {
+            let tree = KdTree::with_metric(points.clone(), metric).unwrap();
+
+            for query in 0..points.len() {
+                let expected = (0..points.len())
+                    .filter(|&idx| idx != query)
+                    .map(|idx| (metric.distance(&points[query], &points[idx]).unwrap(), idx))
+                    .sorted()
+                    .take(k)
+                    .collect_vec();
+
+                prop_assert_eq!(tree.nearest(query, k), expected);
+            }
+        }
+
+        #[test]
+        fn test_closest_pairs_matches_brute_force(points in points_strategy(), metric in metric_strategy()) {
+            let tree = KdTree::with_metric(points.clone(), metric).unwrap();
+
+            let expected = (0..points.len())
+                .tuple_combinations()
+                .map(|(i, j)| (metric.distance(&points[i], &points[j]).unwrap(), i, j))
+                .sorted()
+                .collect_vec();
+
+            prop_assert_eq!(tree.closest_pairs().collect_vec(), expected);
+        }
+    }
+}
@@ -0,0 +1,112 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::str::FromStr;
+
+/// Overrides for a puzzle's parameters, given as `name=value` pairs.
+/// Each day decides which names it understands, what type they are, and what they default to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Params {
+    values: BTreeMap<String, String>,
+}
+
+impl Params {
+    pub fn new() -> Self {
+        Params::default()
+    }
+
+    /// Parse a list of `name=value` assignments. Later assignments win.
+    pub fn parse<S: AsRef<str>>(assignments: &[S]) -> Result<Self, String> {
+        let mut params = Params::new();
+        for assignment in assignments {
+            let assignment = assignment.as_ref();
+            let (name, value) = assignment
+                .split_once('=')
+                .filter(|(name, _)| !name.trim().is_empty())
+                .ok_or_else(|| {
+                    format!("Expected a parameter like name=value, got {:?}", assignment)
+                })?;
+            params = params.with(name.trim(), value.trim());
+        }
+
+        Ok(params)
+    }
+
+    pub fn with(mut self, name: &str, value: impl ToString) -> Self {
+        self.values.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// The value of the parameter `name`, or `default` if it wasn't given.
+    pub fn get<T>(&self, name: &str, default: T) -> Result<T, String>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.values.get(name).map_or(Ok(default), |value| {
+            value
+                .parse()
+                .map_err(|e| format!("Bad value {:?} for parameter {}: {}", value, name, e))
+        })
+    }
+
+    /// Fail if any of the parameters isn't one of the `known` names.
+    pub fn only(&self, known: &[&str]) -> Result<(), String> {
+        match self
+            .values
+            .keys()
+            .find(|name| !known.contains(&name.as_str()))
+        {
+            Some(name) if known.is_empty() => {
+                Err(format!("Unknown parameter {}, this day takes none", name))
+            }
+            Some(name) => Err(format!(
+                "Unknown parameter {}, expected one of: {}",
+                name,
+                known.join(", ")
+            )),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_and_get() {
+        let params = Params::parse(&["connections=10", "size = 3", "size=5"]).unwrap();
+
+        assert_eq!(params.get("connections", 1000usize), Ok(10));
+        assert_eq!(params.get("size", 0i64), Ok(5));
+        assert_eq!(params.get("missing", 7u8), Ok(7));
+        assert_eq!(
+            params.get("connections", true),
+            Err(
+                "Bad value \"10\" for parameter connections: provided string was not `true` or `false`"
+                    .to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_parse_bad_assignment() {
+        assert!(Params::parse(&["connections"]).is_err());
+        assert!(Params::parse(&["=10"]).is_err());
+    }
+
+    #[test]
+    fn test_only() {
+        let params = Params::new().with("threshold", 3);
+
+        assert_eq!(params.only(&["threshold"]), Ok(()));
+        assert_eq!(
+            params.only(&["connections"]),
+            Err("Unknown parameter threshold, expected one of: connections".to_string())
+        );
+        assert_eq!(
+            params.only(&[]),
+            Err("Unknown parameter threshold, this day takes none".to_string())
+        );
+    }
+}
@@ -0,0 +1,134 @@
+/// A disjoint-set forest over the elements `0..n`,
+/// with path compression and union by size.
+#[derive(Debug, Clone)]
+pub struct UnionFind {
+    parent: Vec<usize>,
+    size: Vec<usize>,
+    components: usize,
+}
+
+impl UnionFind {
+    /// Create `n` singleton components.
+    pub fn new(n: usize) -> Self {
+        UnionFind {
+            parent: (0..n).collect(),
+            size: vec![1; n],
+            components: n,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.parent.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.parent.is_empty()
+    }
+
+    /// The representative element of the component containing `x`.
+    pub fn find(&mut self, x: usize) -> usize {
+        let mut root = x;
+        while self.parent[root] != root {
+            root = self.parent[root];
+        }
+
+        // Point everything on the path straight at the root
+        let mut current = x;
+        while self.parent[current] != root {
+            let next = self.parent[current];
+            self.parent[current] = root;
+            current = next;
+        }
+
+        root
+    }
+
+    /// Merge the components containing `a` and `b`.
+    /// Returns whether they were previously separate.
+    pub fn union(&mut self, a: usize, b: usize) -> bool {
+        let (mut a, mut b) = (self.find(a), self.find(b));
+        if a == b {
+            return false;
+        }
+
+        if self.size[a] < self.size[b] {
+            (a, b) = (b, a);
+        }
+        self.parent[b] = a;
+        self.size[a] += self.size[b];
+        self.components -= 1;
+
+        true
+    }
+
+    pub fn connected(&mut self, a: usize, b: usize) -> bool {
+        self.find(a) == self.find(b)
+    }
+
+    pub fn component_count(&self) -> usize {
+        self.components
+    }
+
+    /// The number of elements in the component containing `x`.
+    pub fn component_size(&mut self, x: usize) -> usize {
+        let root = self.find(x);
+        self.size[root]
+    }
+
+    /// The sizes of every component, in no particular order.
+    pub fn component_sizes(&self) -> Vec<usize> {
+        self.parent
+            .iter()
+            .enumerate()
+            .filter_map(|(x, &p)| (x == p).then_some(self.size[x]))
+            .collect()
+    }
+
+    /// The elements of every component, each in increasing order,
+    /// with the components ordered by their smallest element.
+    pub fn groups(&self) -> Vec<Vec<usize>> {
+        let mut groups: Vec<Vec<usize>> = vec![];
+        let mut group_of_root = vec![None; self.len()];
+
+        for x in 0..self.len() {
+            let mut root = x;
+            while self.parent[root] != root {
+                root = self.parent[root];
+            }
+
+            let group = *group_of_root[root].get_or_insert_with(|| {
+                groups.push(vec![]);
+                groups.len() - 1
+            });
+            groups[group].push(x);
+        }
+
+        groups
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    #[test]
+    fn test_union() {
+        let mut uf = UnionFind::new(5);
+
+        assert!(uf.union(0, 1));
+        assert!(uf.union(3, 4));
+        assert!(uf.union(1, 4));
+        assert!(!uf.union(0, 3));
+
+        assert!(uf.connected(0, 4));
+        assert!(!uf.connected(2, 4));
+        assert_eq!(uf.component_count(), 2);
+        assert_eq!(uf.component_size(3), 4);
+        assert_eq!(
+            uf.component_sizes().into_iter().sorted().collect_vec(),
+            vec![1, 4]
+        );
+        assert_eq!(uf.groups(), vec![vec![0, 1, 3, 4], vec![2]]);
+    }
+}
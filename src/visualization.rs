@@ -0,0 +1,142 @@
+use std::error::Error;
+#[cfg(feature = "cli")]
+use std::{
+    fs::{create_dir_all, File},
+    io::{stdout, BufWriter, Write},
+    path::Path,
+    thread::sleep,
+    time::Duration,
+};
+
+pub type Rgb = (u8, u8, u8);
+
+pub type VisualizerResult = Result<Vec<Frame>, Box<dyn Error>>;
+
+pub const BACKGROUND: Rgb = (15, 15, 35);
+pub const DIM: Rgb = (60, 60, 80);
+pub const WHITE: Rgb = (204, 204, 204);
+pub const GREEN: Rgb = (0, 153, 0);
+pub const RED: Rgb = (204, 51, 51);
+pub const GOLD: Rgb = (255, 255, 102);
+
+/// A single frame of an animation: a rectangle of coloured cells.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    width: usize,
+    height: usize,
+    cells: Vec<Rgb>,
+}
+
+impl Frame {
+    pub fn new(width: usize, height: usize) -> Self {
+        Frame {
+            width,
+            height,
+            cells: vec![BACKGROUND; width * height],
+        }
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    pub fn get(&self, x: usize, y: usize) -> Option<Rgb> {
+        (x < self.width && y < self.height).then(|| self.cells[y * self.width + x])
+    }
+
+    /// Colour the cell at `(x, y)`, ignoring coordinates outside the frame.
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        if x < self.width && y < self.height {
+            self.cells[y * self.width + x] = colour;
+        }
+    }
+
+    /// Render the frame with 24-bit ANSI background colours, two terminal columns per cell.
+    pub fn to_ansi(&self) -> String {
+        let mut out = String::new();
+        for row in self.cells.chunks(self.width.max(1)) {
+            for (r, g, b) in row {
+                out.push_str(&format!("\x1b[48;2;{};{};{}m  ", r, g, b));
+            }
+            out.push_str("\x1b[0m\n");
+        }
+        out
+    }
+
+    /// Encode the frame as a binary PPM (P6) image, with each cell drawn as a `scale`×`scale` square.
+    pub fn to_ppm(&self, scale: usize) -> Vec<u8> {
+        let scale = scale.max(1);
+        let mut out =
+            format!("P6\n{} {}\n255\n", self.width * scale, self.height * scale).into_bytes();
+        for row in self.cells.chunks(self.width.max(1)) {
+            let line = row
+                .iter()
+                .flat_map(|&(r, g, b)| [r, g, b].repeat(scale))
+                .collect::<Vec<u8>>();
+            for _ in 0..scale {
+                out.extend_from_slice(&line);
+            }
+        }
+        out
+    }
+}
+
+/// Play the frames in the terminal, redrawing in place.
+#[cfg(feature = "cli")]
+pub fn play(frames: &[Frame], delay: Duration) -> std::io::Result<()> {
+    let mut out = BufWriter::new(stdout().lock());
+    for frame in frames {
+        // Clear the screen and move the cursor to the top left before drawing each frame
+        write!(out, "\x1b[2J\x1b[H{}", frame.to_ansi())?;
+        out.flush()?;
+        sleep(delay);
+    }
+
+    Ok(())
+}
+
+/// Write the frames to `dir` as `frame_0000.ppm`, `frame_0001.ppm`, ...
+#[cfg(feature = "cli")]
+pub fn write_ppm_frames(frames: &[Frame], dir: &Path, scale: usize) -> std::io::Result<()> {
+    create_dir_all(dir)?;
+    for (idx, frame) in frames.iter().enumerate() {
+        let mut file = File::create(dir.join(format!("frame_{:04}.ppm", idx)))?;
+        file.write_all(&frame.to_ppm(scale))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_ignores_out_of_bounds() {
+        let mut frame = Frame::new(2, 2);
+        frame.set(1, 0, RED);
+        frame.set(2, 0, RED);
+
+        assert_eq!(frame.get(1, 0), Some(RED));
+        assert_eq!(frame.get(0, 0), Some(BACKGROUND));
+        assert_eq!(frame.get(2, 0), None);
+    }
+
+    #[test]
+    fn test_to_ppm() {
+        let mut frame = Frame::new(2, 1);
+        frame.set(0, 0, (1, 2, 3));
+        frame.set(1, 0, (4, 5, 6));
+
+        let mut expected = b"P6\n4 2\n255\n".to_vec();
+        for _ in 0..2 {
+            expected.extend_from_slice(&[1, 2, 3, 1, 2, 3, 4, 5, 6, 4, 5, 6]);
+        }
+
+        assert_eq!(frame.to_ppm(2), expected);
+    }
+}
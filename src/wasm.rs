@@ -0,0 +1,12 @@
+use crate::utils::Part;
+use wasm_bindgen::prelude::*;
+
+/// Solve one part of the puzzle for the given day, returning the answer as a string.
+#[wasm_bindgen]
+pub fn solve(day: u8, part: u8, input: &str) -> Result<String, JsError> {
+    let part = Part::try_from(part).map_err(|e| JsError::new(&e))?;
+
+    crate::solve_str(day, part, input)
+        .map(|answer| answer.to_string())
+        .map_err(|e| JsError::new(&e.to_string()))
+}